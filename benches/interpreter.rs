@@ -0,0 +1,49 @@
+// compares `RamMachine::run_with` with the compiled interpreter on the insertion sort example
+//
+//     cargo bench --bench interpreter [-- <count>]
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use rusty_tape::parser::Parser;
+use rusty_tape::ram::compiled::{CompiledMachine, CompiledProgram};
+use rusty_tape::ram::machine::RamMachine;
+use rusty_tape::ram::run::RunOptions;
+
+const SORT: &str = include_str!("../data/programs/sort.ram");
+
+fn measure(name: &str, run: impl FnOnce() -> usize) -> Duration {
+    let start = Instant::now();
+    let steps = run();
+    let elapsed = start.elapsed();
+    println!(
+        "{:<12} {:>10.3} s {:>12} steps {:>8.1} ns/step",
+        name, elapsed.as_secs_f64(), steps, elapsed.as_nanos() as f64 / steps.max(1) as f64,
+    );
+    elapsed
+}
+
+fn main() {
+    let count = std::env::args().skip(1)
+        .find_map(|arg| arg.parse::<i32>().ok())
+        .unwrap_or(3_000);
+
+    // numbers in descending order are the worst case for insertion sort
+    let mut input: Vec<i32> = (1..=count).rev().collect();
+    input.push(0);
+
+    let program = Arc::new(Parser::new().parse_program(SORT).unwrap());
+    let compiled = Arc::new(CompiledProgram::compile(&program));
+
+    println!("sorting {} numbers", count);
+
+    let mut machine = RamMachine::from_program(Arc::clone(&program)).with_input(input.clone());
+    let interpreted = measure("run_with", || machine.run_with(RunOptions::new()).stats.steps);
+
+    let mut fast = CompiledMachine::new(compiled, input);
+    let compiled = measure("compiled", || {
+        fast.run(None);
+        fast.get_steps()
+    });
+
+    assert_eq!(fast.get_output(), machine.get_output());
+    println!("speedup      {:>10.1}x", interpreted.as_secs_f64() / compiled.as_secs_f64());
+}
@@ -0,0 +1,210 @@
+use std::fmt::Write;
+use crate::ram::instruction_op::InstructionOp;
+use crate::ram::program::Program;
+use crate::ram::types::{Index, Label};
+
+// a straight run of instructions, only the first one can be jumped to
+// and only the last one can jump away
+#[derive(Debug, Clone, PartialEq)]
+pub struct BasicBlock {
+    pub labels: Vec<Label>,
+    // indices of the instructions, empty lines left out
+    pub instructions: Vec<Index>,
+    pub code: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Node {
+    Block(usize),
+    // halting or running past the last instruction
+    End,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeKind {
+    // falling through or an unconditional jump
+    Always,
+    Taken,
+    NotTaken,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edge {
+    pub from: usize,
+    pub to: Node,
+    pub kind: EdgeKind,
+}
+
+impl EdgeKind {
+    fn label(self) -> Option<&'static str> {
+        match self {
+            EdgeKind::Always => None,
+            EdgeKind::Taken => Some("yes"),
+            EdgeKind::NotTaken => Some("no"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ControlFlowGraph {
+    // the first block is where the program starts
+    pub blocks: Vec<BasicBlock>,
+    pub edges: Vec<Edge>,
+}
+
+impl ControlFlowGraph {
+    pub fn build(program: &Program) -> ControlFlowGraph {
+        let instructions = program.instructions();
+        let len = instructions.len();
+        let is_empty = |index: Index| instructions[index].op == InstructionOp::Empty;
+
+        // first non-empty instruction at or after every index, jumps to empty lines continue there
+        let mut skip = vec![len; len + 1];
+        for i in (0..len).rev() {
+            skip[i] = if is_empty(i) { skip[i + 1] } else { i };
+        }
+        let target = |label: &Label| program.label(label).map(|index| skip[index]);
+
+        // a block starts at the first instruction, at every jump target and labeled instruction,
+        // and after every jump or halt
+        let mut leader = vec![false; len + 1];
+        leader[skip[0]] = true;
+        for (i, instruction) in instructions.iter().enumerate() {
+            if !instruction.labels.is_empty() {
+                leader[skip[i]] = true;
+            }
+            if let Some(index) = instruction.op.target_label().and_then(target) {
+                leader[index] = true;
+            }
+            if instruction.op == InstructionOp::Halt || instruction.op.target_label().is_some() {
+                leader[skip[i + 1]] = true;
+            }
+        }
+
+        let mut blocks: Vec<BasicBlock> = Vec::new();
+        let mut block_of = vec![0; len + 1];
+        for (i, instruction) in instructions.iter().enumerate().filter(|&(i, _)| !is_empty(i)) {
+            if leader[i] {
+                blocks.push(BasicBlock { labels: Vec::new(), instructions: Vec::new(), code: Vec::new() });
+            }
+            let Some(block) = blocks.last_mut() else {
+                continue;
+            };
+            block.instructions.push(i);
+            block.code.push(instruction.op.to_string());
+            block_of[i] = blocks.len() - 1;
+        }
+        // labels of empty lines belong to the block they lead to
+        for (i, instruction) in instructions.iter().enumerate() {
+            if skip[i] < len {
+                blocks[block_of[skip[i]]].labels.extend(instruction.labels.iter().cloned());
+            }
+        }
+
+        let node = |index: Index| if index < len { Node::Block(block_of[index]) } else { Node::End };
+        let mut edges = Vec::new();
+        for (from, block) in blocks.iter().enumerate() {
+            let Some(&last) = block.instructions.last() else {
+                continue;
+            };
+            let op = &instructions[last].op;
+            let next = node(skip[last + 1]);
+            let mut edge = |to: Node, kind: EdgeKind| edges.push(Edge { from, to, kind });
+            match op {
+                InstructionOp::Halt => edge(Node::End, EdgeKind::Always),
+                // a jump to a missing label fails, there is nowhere to go
+                InstructionOp::Jump(label) => {
+                    if let Some(index) = target(label) {
+                        edge(node(index), EdgeKind::Always);
+                    }
+                }
+                _ => match op.target_label().map(&target) {
+                    Some(Some(index)) if node(index) == next => edge(next, EdgeKind::Always),
+                    Some(taken) => {
+                        if let Some(index) = taken {
+                            edge(node(index), EdgeKind::Taken);
+                        }
+                        edge(next, EdgeKind::NotTaken);
+                    }
+                    None => edge(next, EdgeKind::Always),
+                },
+            }
+        }
+
+        ControlFlowGraph { blocks, edges }
+    }
+
+    // block holding the instruction, empty lines belong to none
+    pub fn block_of(&self, index: Index) -> Option<usize> {
+        self.blocks.iter().position(|block| block.instructions.contains(&index))
+    }
+
+    pub fn edges_from(&self, block: usize) -> impl Iterator<Item = &Edge> {
+        self.edges.iter().filter(move |edge| edge.from == block)
+    }
+
+    // Graphviz, rendered with `dot -Tsvg`
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph program {\n");
+        dot.push_str("    node [shape=box, fontname=\"monospace\"];\n");
+        dot.push_str("    start [shape=oval];\n    end [shape=oval];\n");
+        for (id, block) in self.blocks.iter().enumerate() {
+            // `\l` ends a left-aligned line
+            let text: String = block_lines(block).iter().map(|line| format!("{}\\l", escape_dot(line))).collect();
+            let _ = writeln!(dot, "    b{} [label=\"{}\"];", id, text);
+        }
+        if !self.blocks.is_empty() {
+            dot.push_str("    start -> b0;\n");
+        }
+        for edge in &self.edges {
+            let to = match edge.to {
+                Node::Block(id) => format!("b{}", id),
+                Node::End => "end".to_string(),
+            };
+            let _ = match edge.kind.label() {
+                Some(label) => writeln!(dot, "    b{} -> {} [label=\"{}\"];", edge.from, to, label),
+                None => writeln!(dot, "    b{} -> {};", edge.from, to),
+            };
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    // for Markdown documents and slides, `end` is a keyword there so the node is called `stop`
+    pub fn to_mermaid(&self) -> String {
+        let mut mermaid = String::from("flowchart TD\n");
+        mermaid.push_str("    start([start])\n    stop([end])\n");
+        for (id, block) in self.blocks.iter().enumerate() {
+            let text: Vec<String> = block_lines(block).iter().map(|line| escape_mermaid(line)).collect();
+            let _ = writeln!(mermaid, "    b{}[\"{}\"]", id, text.join("<br/>"));
+        }
+        if !self.blocks.is_empty() {
+            mermaid.push_str("    start --> b0\n");
+        }
+        for edge in &self.edges {
+            let to = match edge.to {
+                Node::Block(id) => format!("b{}", id),
+                Node::End => "stop".to_string(),
+            };
+            let _ = match edge.kind.label() {
+                Some(label) => writeln!(mermaid, "    b{} -->|{}| {}", edge.from, label, to),
+                None => writeln!(mermaid, "    b{} --> {}", edge.from, to),
+            };
+        }
+        mermaid
+    }
+}
+
+// the labels on a line of their own above the code
+fn block_lines(block: &BasicBlock) -> Vec<String> {
+    let labels = block.labels.iter().map(|label| format!("{}:", label));
+    labels.chain(block.code.iter().cloned()).collect()
+}
+
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn escape_mermaid(text: &str) -> String {
+    text.replace('"', "#quot;").replace('<', "#lt;").replace('>', "#gt;")
+}
@@ -0,0 +1,442 @@
+use std::io::{BufRead, Read, Write};
+use std::path::Path;
+use std::sync::Arc;
+use crate::cfg::ControlFlowGraph;
+use crate::lint::{self, Severity};
+use crate::parser::Parser;
+use crate::ram::batch::{self, BatchOptions};
+use crate::ram::compiled::{CompiledMachine, CompiledProgram};
+use crate::ram::dialect::Dialect;
+use crate::ram::execution_mode::ExecutionMode;
+use crate::ram::io_mode::IoMode;
+use crate::ram::machine::RamMachine;
+use crate::ram::rasp;
+use crate::ram::run::{RunOptions, RunOutcome};
+use crate::ram::strict_mode::StrictMode;
+use crate::ram::types::{Index, Number};
+use crate::ui::handler::UiHandler;
+
+const USAGE: &str = "Usage:
+  rusty_tape [paths...]            start the interactive menu, scanning the given
+                                   directories, files or glob patterns for programs
+  rusty_tape run <file> [options]  run a program without the UI
+  rusty_tape batch <file> [options]
+                                   run a program on many input tapes in parallel
+  rusty_tape lint <file> [--dialect <name>]
+                                   check a program for likely mistakes without running it
+  rusty_tape cfg <file> [--format <format>] [--dialect <name>]
+                                   print the control-flow graph of a program as
+                                   `dot` (default, for Graphviz) or `mermaid`
+
+Run options:
+  --input <values>      input tape, values separated by commas or spaces
+  --input-file <path>   read the input tape from a file
+  --stdin               read the input tape from stdin
+  --interactive         prompt for values on stdin once the input tape runs out
+  --profile <format>    print execution counts, branches and memory accesses
+                        to stderr as `text` or `json`
+  --max-steps <n>       fail once the program has executed n instructions
+  --detect-loops        fail as soon as the machine state repeats
+  --strict <level>      check reads of uninitialized cells and invalid addresses,
+                        `warn` prints warnings to stderr, `error` stops the program
+  --memory-limit <n>    addresses from n up are invalid in strict mode
+  --rasp                run the program stored in memory from address 1000 up
+  --rasp-base <n>       run the program stored in memory from address n up
+  --fast                run precompiled bytecode, cannot be combined with
+                        --interactive, --profile, --detect-loops, --strict or --rasp
+  --dialect <name>      `extended` (default) or `textbook`, which only accepts
+                        the classic instruction forms
+
+Without an input option, the `# input:` header of the program is used.
+
+Batch options:
+  --inputs <path>       input tapes, one per line (default: stdin)
+  --max-steps <n>       step limit of every run (default: 1000000)
+  --threads <n>         number of worker threads (default: one per core)
+  --dialect <name>      `extended` (default) or `textbook`";
+
+const BATCH_STEP_LIMIT: Index = 1_000_000;
+
+enum InputSource {
+    Header,
+    Values(String),
+    File(String),
+    Stdin,
+}
+
+enum ProfileFormat {
+    Text,
+    Json,
+}
+
+struct BatchArgs {
+    file: String,
+    inputs: Option<String>,
+    max_steps: Index,
+    threads: Option<usize>,
+    dialect: Dialect,
+}
+
+struct RunArgs {
+    file: String,
+    input: InputSource,
+    io_mode: IoMode,
+    profile: Option<ProfileFormat>,
+    max_steps: Option<Index>,
+    detect_loops: bool,
+    strict_mode: StrictMode,
+    memory_limit: Option<Number>,
+    fast: bool,
+    dialect: Dialect,
+    mode: ExecutionMode,
+}
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    match args.first().map(String::as_str) {
+        None => UiHandler::default().run().map_err(|e| e.to_string()),
+        Some("run") => run_headless(parse_run_args(&args[1..])?),
+        Some("batch") => run_batch(parse_batch_args(&args[1..])?),
+        Some("lint") => run_lint(&args[1..]),
+        Some("cfg") => run_cfg(&args[1..]),
+        Some("help") | Some("--help") | Some("-h") => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        Some(path) if is_path(path) => {
+            // a typo in any of the paths is reported instead of silently finding nothing there
+            if let Some(missing) = args.iter().find(|arg| !is_path(arg)) {
+                return Err(format!("No such file or directory {}", missing));
+            }
+            UiHandler::with_paths(args.to_vec()).run().map_err(|e| e.to_string())
+        }
+        Some(other) => Err(format!("Unknown command {}\n\n{}", other, USAGE)),
+    }
+}
+
+// an existing file or directory, or a glob pattern that is matched later
+fn is_path(arg: &str) -> bool {
+    Path::new(arg).exists() || arg.contains(['*', '?'])
+}
+
+fn parse_run_args(args: &[String]) -> Result<RunArgs, String> {
+    let mut file = None;
+    let mut input = InputSource::Header;
+    let mut io_mode = IoMode::Tape;
+    let mut profile = None;
+    let mut max_steps = None;
+    let mut detect_loops = false;
+    let mut strict_mode = StrictMode::Off;
+    let mut memory_limit = None;
+    let mut fast = false;
+    let mut dialect = Dialect::default();
+    let mut mode = ExecutionMode::Ram;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next()
+            .cloned()
+            .ok_or_else(|| format!("Missing value for {}", name));
+
+        match arg.as_str() {
+            "--input" => input = InputSource::Values(value(arg)?),
+            "--input-file" => input = InputSource::File(value(arg)?),
+            "--stdin" => input = InputSource::Stdin,
+            "--interactive" => io_mode = IoMode::Interactive,
+            "--profile" => profile = Some(match value(arg)?.as_str() {
+                "text" => ProfileFormat::Text,
+                "json" => ProfileFormat::Json,
+                other => return Err(format!("Unknown profile format {}, expected text or json", other)),
+            }),
+            "--max-steps" => {
+                let steps = value(arg)?;
+                max_steps = Some(steps.parse::<Index>().map_err(|_| format!("Invalid step limit {}", steps))?);
+            }
+            "--detect-loops" => detect_loops = true,
+            "--strict" => strict_mode = match value(arg)?.as_str() {
+                "warn" => StrictMode::Warn,
+                "error" => StrictMode::Error,
+                other => return Err(format!("Unknown strict level {}, expected warn or error", other)),
+            },
+            "--memory-limit" => {
+                let limit = value(arg)?;
+                memory_limit = Some(limit.parse::<Number>().map_err(|_| format!("Invalid memory limit {}", limit))?);
+            }
+            "--fast" => fast = true,
+            "--dialect" => dialect = parse_dialect(&value(arg)?)?,
+            "--rasp" => mode = ExecutionMode::Rasp(rasp::DEFAULT_BASE),
+            "--rasp-base" => {
+                let base = value(arg)?;
+                mode = ExecutionMode::Rasp(base.parse::<Number>().map_err(|_| format!("Invalid RASP base {}", base))?);
+            }
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {}\n\n{}", arg, USAGE)),
+            _ if file.is_none() => file = Some(arg.clone()),
+            _ => return Err(format!("Unexpected argument {}\n\n{}", arg, USAGE)),
+        }
+    }
+
+    let file = file.ok_or_else(|| format!("Missing program file\n\n{}", USAGE))?;
+    if fast && (io_mode == IoMode::Interactive || profile.is_some() || detect_loops || strict_mode != StrictMode::Off || mode != ExecutionMode::Ram) {
+        return Err("--fast cannot be combined with --interactive, --profile, --detect-loops, --strict or --rasp".to_string());
+    }
+    // the tape is read from stdin to its end, nothing would be left to answer the prompts
+    if matches!(input, InputSource::Stdin) && io_mode == IoMode::Interactive {
+        return Err("--stdin cannot be combined with --interactive".to_string());
+    }
+    Ok(RunArgs { file, input, io_mode, profile, max_steps, detect_loops, strict_mode, memory_limit, fast, dialect, mode })
+}
+
+fn parse_batch_args(args: &[String]) -> Result<BatchArgs, String> {
+    let mut file = None;
+    let mut inputs = None;
+    let mut max_steps = BATCH_STEP_LIMIT;
+    let mut threads = None;
+    let mut dialect = Dialect::default();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next()
+            .cloned()
+            .ok_or_else(|| format!("Missing value for {}", name));
+
+        match arg.as_str() {
+            "--inputs" => inputs = Some(value(arg)?),
+            "--max-steps" => {
+                let steps = value(arg)?;
+                max_steps = steps.parse().map_err(|_| format!("Invalid step limit {}", steps))?;
+            }
+            "--threads" => {
+                let count = value(arg)?;
+                threads = Some(count.parse().map_err(|_| format!("Invalid thread count {}", count))?);
+            }
+            "--dialect" => dialect = parse_dialect(&value(arg)?)?,
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {}\n\n{}", arg, USAGE)),
+            _ if file.is_none() => file = Some(arg.clone()),
+            _ => return Err(format!("Unexpected argument {}\n\n{}", arg, USAGE)),
+        }
+    }
+
+    let file = file.ok_or_else(|| format!("Missing program file\n\n{}", USAGE))?;
+    Ok(BatchArgs { file, inputs, max_steps, threads, dialect })
+}
+
+fn run_lint(args: &[String]) -> Result<(), String> {
+    let mut file = None;
+    let mut dialect = Dialect::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dialect" => dialect = parse_dialect(args.next().ok_or("Missing value for --dialect")?)?,
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {}\n\n{}", arg, USAGE)),
+            _ if file.is_none() => file = Some(arg.clone()),
+            _ => return Err(format!("Unexpected argument {}\n\n{}", arg, USAGE)),
+        }
+    }
+    let file = file.ok_or_else(|| format!("Missing program file\n\n{}", USAGE))?;
+
+    let lints = lint::lint(&Parser::new().with_dialect(dialect).parse_program_file(&file)?);
+    for lint in &lints {
+        println!("{}", lint);
+    }
+    // infos alone do not fail the check
+    let problems = lints.iter().filter(|lint| lint.severity != Severity::Info).count();
+    match problems {
+        0 if lints.is_empty() => println!("No problems found"),
+        0 => {}
+        1 => return Err("1 problem found".to_string()),
+        _ => return Err(format!("{} problems found", problems)),
+    }
+    Ok(())
+}
+
+fn run_cfg(args: &[String]) -> Result<(), String> {
+    let mut file = None;
+    let mut mermaid = false;
+    let mut dialect = Dialect::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => mermaid = match args.next().ok_or("Missing value for --format")?.as_str() {
+                "dot" => false,
+                "mermaid" => true,
+                other => return Err(format!("Unknown graph format {}, expected dot or mermaid", other)),
+            },
+            "--dialect" => dialect = parse_dialect(args.next().ok_or("Missing value for --dialect")?)?,
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {}\n\n{}", arg, USAGE)),
+            _ if file.is_none() => file = Some(arg.clone()),
+            _ => return Err(format!("Unexpected argument {}\n\n{}", arg, USAGE)),
+        }
+    }
+    let file = file.ok_or_else(|| format!("Missing program file\n\n{}", USAGE))?;
+
+    let cfg = ControlFlowGraph::build(&Parser::new().with_dialect(dialect).parse_program_file(&file)?);
+    print!("{}", if mermaid { cfg.to_mermaid() } else { cfg.to_dot() });
+    Ok(())
+}
+
+fn parse_dialect(name: &str) -> Result<Dialect, String> {
+    match name {
+        "extended" => Ok(Dialect::Extended),
+        "textbook" => Ok(Dialect::Textbook),
+        other => Err(format!("Unknown dialect {}, expected extended or textbook", other)),
+    }
+}
+
+fn read_input(args: &RunArgs) -> Result<Vec<Number>, String> {
+    match &args.input {
+        InputSource::Header => {
+            let source = std::fs::read_to_string(&args.file).map_err(|e| e.to_string())?;
+            Parser::parse_input_directive(&source).unwrap_or(Ok(Vec::new()))
+        }
+        InputSource::Values(values) => Parser::parse_tape(values),
+        InputSource::File(path) => Parser::parse_tape_file(path),
+        InputSource::Stdin => {
+            let mut contents = String::new();
+            std::io::stdin().read_to_string(&mut contents).map_err(|e| e.to_string())?;
+            Parser::parse_tape(&contents)
+        }
+    }
+}
+
+fn run_headless(args: RunArgs) -> Result<(), String> {
+    let input = read_input(&args)?;
+    if args.fast {
+        return run_compiled(&args, input);
+    }
+    let mut machine = Parser::new().with_dialect(args.dialect).parse_file(&args.file)?
+        .with_input(input)
+        .with_io_mode(args.io_mode);
+    if args.profile.is_some() {
+        machine = machine.with_profiling();
+    }
+    if args.detect_loops {
+        machine = machine.with_loop_detection();
+    }
+    machine = machine.with_strict_mode(args.strict_mode);
+    if let Some(limit) = args.memory_limit {
+        machine = machine.with_memory_limit(limit);
+    }
+    machine = machine.with_mode(args.mode)?;
+
+    let result = execute(&mut machine, args.max_steps);
+    for warning in machine.get_warnings() {
+        eprintln!("Warning: {}", warning);
+    }
+
+    // the profile is printed even when the program fails, it shows how far it got
+    if let (Some(format), Some(profile)) = (&args.profile, machine.get_profile()) {
+        match format {
+            ProfileFormat::Text => eprint!("{}", profile.to_text(machine.get_shared_program())),
+            ProfileFormat::Json => eprintln!("{}", profile.to_json(machine.get_shared_program())),
+        }
+    }
+    result?;
+
+    println!("{}", format_tape(machine.get_output()));
+    Ok(())
+}
+
+fn run_compiled(args: &RunArgs, input: Vec<Number>) -> Result<(), String> {
+    let program = CompiledProgram::compile(&Parser::new().with_dialect(args.dialect).parse_program_file(&args.file)?);
+    let mut machine = CompiledMachine::new(Arc::new(program), input);
+    match machine.run(args.max_steps) {
+        RunOutcome::Error(message) => return Err(message),
+        RunOutcome::LimitExceeded => return Err(format!("Step limit exceeded after {} steps", machine.get_steps())),
+        _ => {}
+    }
+    println!("{}", format_tape(machine.get_output()));
+    Ok(())
+}
+
+fn execute(machine: &mut RamMachine, max_steps: Option<Index>) -> Result<(), String> {
+    loop {
+        // the limit covers the whole program, not only the steps since the last input
+        let mut options = RunOptions::new();
+        if let Some(limit) = max_steps {
+            options = options.with_step_limit(limit.saturating_sub(machine.get_instruction_count()));
+        }
+        match machine.run_with(options).outcome {
+            RunOutcome::AwaitingInput => prompt_input(machine)?,
+            RunOutcome::Error(message) => return Err(message),
+            RunOutcome::LimitExceeded => {
+                return Err(format!("Step limit exceeded after {} steps", machine.get_instruction_count()));
+            }
+            _ => return Ok(()),
+        }
+    }
+}
+
+fn run_batch(args: BatchArgs) -> Result<(), String> {
+    let contents = match &args.inputs {
+        Some(path) => std::fs::read_to_string(path).map_err(|e| e.to_string())?,
+        None => {
+            let mut contents = String::new();
+            std::io::stdin().read_to_string(&mut contents).map_err(|e| e.to_string())?;
+            contents
+        }
+    };
+    // one tape per line, blank lines and comments are skipped
+    let inputs = contents.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(i, line)| Parser::parse_tape(line).map_err(|err| format!("Line {}: {}", i + 1, err)))
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let program = Arc::new(Parser::new().with_dialect(args.dialect).parse_program_file(&args.file)?);
+    let mut options = BatchOptions::new().with_step_limit(args.max_steps);
+    if let Some(threads) = args.threads {
+        options = options.with_threads(threads);
+    }
+    let results = batch::run_batch(program, &inputs, options);
+
+    let input_width = inputs.iter().map(|input| format_tape(input).len()).max().unwrap_or(0).max("input".len());
+    let output_width = results.iter().map(|result| format_tape(&result.output).len()).max().unwrap_or(0).max("output".len());
+    println!("{:>4}  {:<input_width$}  {:<output_width$}  {:>10}  result", "#", "input", "output", "steps");
+    for (i, (input, result)) in inputs.iter().zip(&results).enumerate() {
+        let status = match result.error() {
+            Some(error) => error,
+            None => "ok".to_string(),
+        };
+        println!(
+            "{:>4}  {:<input_width$}  {:<output_width$}  {:>10}  {}",
+            i + 1, format_tape(input), format_tape(&result.output), result.steps, status,
+        );
+    }
+
+    let failed = results.iter().filter(|result| result.error().is_some()).count();
+    println!("\n{} runs, {} ok, {} failed", results.len(), results.len() - failed, failed);
+    if failed > 0 {
+        return Err(format!("{} of {} runs failed", failed, results.len()));
+    }
+    Ok(())
+}
+
+fn prompt_input(machine: &mut RamMachine) -> Result<(), String> {
+    // keep asking until the line holds at least one value
+    loop {
+        eprint!("? ");
+        std::io::stderr().flush().map_err(|e| e.to_string())?;
+
+        let mut line = String::new();
+        if std::io::stdin().lock().read_line(&mut line).map_err(|e| e.to_string())? == 0 {
+            return Err("Attempt to read from empty tape".to_string());
+        }
+
+        match Parser::parse_tape(&line) {
+            Ok(values) if values.is_empty() => continue,
+            Ok(values) => {
+                for value in values {
+                    machine.push_input(value);
+                }
+                return Ok(());
+            }
+            Err(err) => eprintln!("{}", err),
+        }
+    }
+}
+
+fn format_tape(tape: &[Number]) -> String {
+    tape.iter()
+        .map(|value| value.to_string())
+        .collect::<Vec<String>>()
+        .join(", ")
+}
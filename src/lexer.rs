@@ -0,0 +1,108 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Identifier,
+    Keyword,
+    Register,
+    Number,
+    Assign,
+    Colon,
+    Operator,
+    Relation,
+    LeftBracket,
+    RightBracket,
+    LeftParen,
+    RightParen,
+    Comment,
+    Whitespace,
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token<'a> {
+    pub kind: TokenKind,
+    pub text: &'a str,
+    pub start: usize,
+}
+
+const KEYWORDS: [&str; 5] = ["goto", "if", "halt", "read", "write"];
+
+// splits a line into tokens, concatenating their text gives back the whole line
+pub fn tokenize(line: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+
+    while start < line.len() {
+        let rest = &line[start..];
+        let c = rest.chars().next().unwrap_or_default();
+
+        let (kind, length) = match c {
+            // `#`, `;` and `//` all start a comment running to the end of the line
+            '#' | ';' => (TokenKind::Comment, rest.len()),
+            '/' if rest.starts_with("//") => (TokenKind::Comment, rest.len()),
+            c if c.is_whitespace() => (TokenKind::Whitespace, prefix_length(rest, char::is_whitespace)),
+            c if c.is_ascii_digit() => (TokenKind::Number, prefix_length(rest, |c| c.is_ascii_digit())),
+            c if c.is_alphabetic() || c == '_' => word(rest),
+            ':' if rest.starts_with(":=") => (TokenKind::Assign, 2),
+            // the textbook forms ∶= (with a ratio sign) and ←
+            '∶' if rest.starts_with("∶=") => (TokenKind::Assign, "∶=".len()),
+            '←' => (TokenKind::Assign, c.len_utf8()),
+            '≤' | '≥' | '≠' => (TokenKind::Relation, c.len_utf8()),
+            ':' => (TokenKind::Colon, 1),
+            '<' | '>' | '=' | '!' if rest[1..].starts_with('=') => (TokenKind::Relation, 2),
+            '<' | '>' => (TokenKind::Relation, 1),
+            '+' | '-' | '*' | '/' => (TokenKind::Operator, 1),
+            '[' => (TokenKind::LeftBracket, 1),
+            ']' => (TokenKind::RightBracket, 1),
+            '(' => (TokenKind::LeftParen, 1),
+            ')' => (TokenKind::RightParen, 1),
+            c => (TokenKind::Unknown, c.len_utf8()),
+        };
+
+        tokens.push(Token { kind, text: &rest[..length], start });
+        start += length;
+    }
+    tokens
+}
+
+// number of tokens at the start of a line without whitespace forming a label, shared by the parser and the highlighter:
+// words separated by whitespace, or joined by `-` or `.` without spaces around them like `loop-2` and `a.b`
+pub fn label_length(tokens: &[Token]) -> usize {
+    let is_word = |token: &Token| matches!(token.kind, TokenKind::Identifier | TokenKind::Number | TokenKind::Register);
+    let touches = |left: &Token, right: &Token| left.start + left.text.len() == right.start;
+
+    let mut length = 0;
+    while tokens.get(length).is_some_and(is_word) {
+        length += 1;
+        if let Some([joiner, word]) = tokens.get(length..length + 2) {
+            if matches!(joiner.text, "-" | ".") && is_word(word) && touches(&tokens[length - 1], joiner) && touches(joiner, word) {
+                length += 1;
+            }
+        }
+    }
+    length
+}
+
+fn prefix_length(input: &str, predicate: impl Fn(char) -> bool) -> usize {
+    input.find(|c| !predicate(c)).unwrap_or(input.len())
+}
+
+fn word(input: &str) -> (TokenKind, usize) {
+    let length = prefix_length(input, |c| c.is_alphanumeric() || c == '_');
+    let text = &input[..length];
+
+    // registers are R or r followed by a number, negative numbers included
+    let register = text.starts_with(['R', 'r']);
+    if text.len() == 1 && register && input[1..].starts_with('-') {
+        let digits = prefix_length(&input[2..], |c| c.is_ascii_digit());
+        if digits > 0 {
+            return (TokenKind::Register, 2 + digits);
+        }
+    }
+    if text.len() > 1 && register && text[1..].chars().all(|c| c.is_ascii_digit()) {
+        return (TokenKind::Register, length);
+    }
+    if KEYWORDS.contains(&text) {
+        return (TokenKind::Keyword, length);
+    }
+    (TokenKind::Identifier, length)
+}
@@ -1,3 +1,7 @@
 pub mod parser;
 pub mod ui;
-pub mod ram;
\ No newline at end of file
+pub mod ram;
+pub mod cli;
+pub mod lexer;
+pub mod lint;
+pub mod cfg;
\ No newline at end of file
@@ -0,0 +1,323 @@
+use std::collections::BTreeSet;
+use std::fmt::Display;
+use crate::ram::expression;
+use crate::ram::instruction_op::InstructionOp;
+use crate::ram::operand::Operand;
+use crate::ram::program::Program;
+use crate::ram::types::{Index, Number};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    // the program fails when it gets there
+    Error,
+    // most likely a mistake
+    Warning,
+    // harmless, but could be cleaned up
+    Info,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Info => write!(f, "info"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lint {
+    // zero-based source line
+    pub line: Index,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Display for Lint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Line {}: {}: {}", self.line + 1, self.severity, self.message)
+    }
+}
+
+// problems found without running the program, sorted by line
+pub fn lint(program: &Program) -> Vec<Lint> {
+    let mut linter = Linter { program, lints: Vec::new() };
+    let reachable = linter.reachable();
+
+    linter.undefined_labels();
+    linter.unreachable_code(&reachable);
+    linter.unwritten_reads(&reachable);
+    linter.dead_stores(&reachable);
+    linter.unused_labels();
+    linter.missing_halt();
+    linter.negative_registers();
+    linter.constant_conditions();
+
+    let mut lints = linter.lints;
+    lints.sort_by_key(|lint| (lint.line, lint.severity));
+    // the instructions of a lowered line can report the same thing
+    lints.dedup();
+    lints
+}
+
+// registers a dataflow fact holds for, `any` stands for every cell
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Cells {
+    registers: BTreeSet<Number>,
+    any: bool,
+}
+
+impl Cells {
+    fn contains(&self, reg: Number) -> bool {
+        self.any || self.registers.contains(&reg)
+    }
+
+    // true when something was added
+    fn merge(&mut self, other: &Cells) -> bool {
+        let before = (self.registers.len(), self.any);
+        self.registers.extend(&other.registers);
+        self.any |= other.any;
+        before != (self.registers.len(), self.any)
+    }
+}
+
+// the cells an instruction reads and writes, indirect accesses can touch any cell
+#[derive(Default)]
+struct Access {
+    reads: Vec<Number>,
+    write: Option<Number>,
+    reads_any: bool,
+    writes_any: bool,
+}
+
+impl Access {
+    fn of(op: &InstructionOp) -> Access {
+        let (target, values) = operands(op);
+        let mut access = Access::default();
+        for value in values {
+            match value {
+                Operand::Constant(_) => {}
+                Operand::Register(reg) => access.reads.push(reg),
+                Operand::Indirect(reg) => {
+                    access.reads.push(reg);
+                    access.reads_any = true;
+                }
+            }
+        }
+        match target {
+            Some(Operand::Register(reg)) => access.write = Some(reg),
+            Some(Operand::Indirect(reg)) => {
+                access.reads.push(reg);
+                access.writes_any = true;
+            }
+            _ => {}
+        }
+        access
+    }
+}
+
+// the cell an instruction assigns to and the values it reads
+fn operands(op: &InstructionOp) -> (Option<Operand>, Vec<Operand>) {
+    match *op {
+        InstructionOp::Assign(t, v) => (Some(t), vec![v]),
+        InstructionOp::Arithmetic(t, a, _, b) => (Some(t), vec![a, b]),
+        InstructionOp::CondJump(a, _, b, _) => (None, vec![a, b]),
+        InstructionOp::Read(t) => (Some(t), Vec::new()),
+        InstructionOp::Write(v) => (None, vec![v]),
+        InstructionOp::Jump(_) | InstructionOp::Halt | InstructionOp::Empty => (None, Vec::new()),
+    }
+}
+
+struct Linter<'a> {
+    program: &'a Program,
+    lints: Vec<Lint>,
+}
+
+impl Linter<'_> {
+    fn report(&mut self, index: Index, severity: Severity, message: String) {
+        self.lints.push(Lint { line: self.program.line(index), severity, message });
+    }
+
+    fn op(&self, index: Index) -> &InstructionOp {
+        &self.program.instructions()[index].op
+    }
+
+    // instructions that run for some input, following every branch from the first one
+    fn reachable(&self) -> Vec<bool> {
+        let mut reachable = vec![false; self.program.len()];
+        let mut pending = vec![0];
+        while let Some(index) = pending.pop() {
+            if index < self.program.len() && !reachable[index] {
+                reachable[index] = true;
+                pending.extend(self.program.successors(index));
+            }
+        }
+        reachable
+    }
+
+    fn undefined_labels(&mut self) {
+        for index in 0..self.program.len() {
+            if let Some(label) = self.op(index).target_label() {
+                if self.program.label(label).is_none() {
+                    let message = format!("Label {} is not defined", label);
+                    self.report(index, Severity::Error, message);
+                }
+            }
+        }
+    }
+
+    // only the first instruction of every unreachable stretch is reported
+    fn unreachable_code(&mut self, reachable: &[bool]) {
+        let mut previous_reachable = true;
+        for (index, &is_reachable) in reachable.iter().enumerate() {
+            if *self.op(index) == InstructionOp::Empty {
+                continue;
+            }
+            if !is_reachable && previous_reachable {
+                self.report(index, Severity::Warning, "This instruction is never reached".to_string());
+            }
+            previous_reachable = is_reachable;
+        }
+    }
+
+    fn unwritten_reads(&mut self, reachable: &[bool]) {
+        // cells that might have been written before each instruction runs
+        let len = self.program.len();
+        let mut written: Vec<Option<Cells>> = vec![None; len + 1];
+        written[0] = Some(Cells::default());
+        let mut pending = vec![0];
+        while let Some(index) = pending.pop() {
+            if index >= len {
+                continue;
+            }
+            let mut after = written[index].clone().unwrap_or_default();
+            let access = Access::of(self.op(index));
+            after.any |= access.writes_any;
+            after.registers.extend(access.write);
+            for next in self.program.successors(index) {
+                match &mut written[next] {
+                    Some(cells) => {
+                        if cells.merge(&after) {
+                            pending.push(next);
+                        }
+                    }
+                    entry => {
+                        *entry = Some(after.clone());
+                        pending.push(next);
+                    }
+                }
+            }
+        }
+
+        for index in (0..len).filter(|&index| reachable[index]) {
+            let Some(cells) = written[index].clone() else {
+                continue;
+            };
+            let access = Access::of(self.op(index));
+            let unwritten: BTreeSet<Number> = access.reads.into_iter().filter(|&reg| !cells.contains(reg)).collect();
+            for reg in unwritten {
+                let message = format!("R{} is read before anything is written to it", reg);
+                self.report(index, Severity::Warning, message);
+            }
+        }
+    }
+
+    fn dead_stores(&mut self, reachable: &[bool]) {
+        // cells that might be read later, before each instruction runs
+        let len = self.program.len();
+        let mut live = vec![Cells::default(); len + 1];
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for index in (0..len).rev().filter(|&index| reachable[index]) {
+                let mut cells = self.live_after(index, &live);
+                let access = Access::of(self.op(index));
+                if let Some(reg) = access.write {
+                    cells.registers.remove(&reg);
+                }
+                cells.registers.extend(access.reads);
+                cells.any |= access.reads_any;
+                changed |= live[index].merge(&cells);
+            }
+        }
+
+        for index in (0..len).filter(|&index| reachable[index]) {
+            let op = self.op(index);
+            // reading also moves along the input tape, so it is never useless
+            if matches!(op, InstructionOp::Read(_)) {
+                continue;
+            }
+            if let Some(reg) = Access::of(op).write {
+                if !self.live_after(index, &live).contains(reg) {
+                    let message = format!("The value written to R{} is never read", reg);
+                    self.report(index, Severity::Info, message);
+                }
+            }
+        }
+    }
+
+    fn live_after(&self, index: Index, live: &[Cells]) -> Cells {
+        let mut cells = Cells::default();
+        for next in self.program.successors(index) {
+            cells.merge(&live[next]);
+        }
+        cells
+    }
+
+    fn unused_labels(&mut self) {
+        let used: BTreeSet<&String> = self.program.instructions().iter()
+            .filter_map(|instruction| instruction.op.target_label())
+            .collect();
+        let mut unused: Vec<(Index, String)> = self.program.labels().iter()
+            .filter(|(label, _)| !used.contains(label))
+            .map(|(label, &index)| (index, label.clone()))
+            .collect();
+        unused.sort();
+        for (index, label) in unused {
+            self.report(index, Severity::Info, format!("Label {} is never used", label));
+        }
+    }
+
+    fn missing_halt(&mut self) {
+        let last = (0..self.program.len()).rev().find(|&index| *self.op(index) != InstructionOp::Empty);
+        if let Some(index) = last {
+            if !matches!(self.op(index), InstructionOp::Halt | InstructionOp::Jump(_)) {
+                self.report(index, Severity::Warning, "The program can run past its end without halt".to_string());
+            }
+        }
+    }
+
+    fn negative_registers(&mut self) {
+        for (index, instruction) in self.program.instructions().iter().enumerate() {
+            let (target, values) = operands(&instruction.op);
+            let negative: BTreeSet<Number> = target.into_iter().chain(values)
+                .filter_map(|operand| match operand {
+                    Operand::Register(reg) | Operand::Indirect(reg) => Some(reg),
+                    Operand::Constant(_) => None,
+                })
+                // the temporaries of lowered expressions are negative on purpose, programs cannot name them
+                .filter(|&reg| reg < 0 && !expression::is_temporary(reg))
+                .collect();
+            for reg in negative {
+                self.report(index, Severity::Warning, format!("Negative register R{}", reg));
+            }
+        }
+    }
+
+    fn constant_conditions(&mut self) {
+        for index in 0..self.program.len() {
+            let InstructionOp::CondJump(a, rel, b, _) = *self.op(index) else {
+                continue;
+            };
+            let result = match (a, b) {
+                (Operand::Constant(a), Operand::Constant(b)) => rel.holds(a, b),
+                // comparing a cell with itself
+                (a, b) if a == b => rel.holds(0, 0),
+                _ => continue,
+            };
+            let message = format!("The condition is always {}", result);
+            self.report(index, Severity::Warning, message);
+        }
+    }
+}
@@ -1,10 +1,15 @@
 pub mod parser;
 pub mod ui;
 pub mod ram;
-
-use crate::ui::handler::UiHandler;
+pub mod cli;
+pub mod lexer;
+pub mod lint;
+pub mod cfg;
 
 fn main() {
-    let mut ui = UiHandler::default();
-    ui.run().expect("Error running UI");
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Err(err) = cli::run(&args) {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
 }
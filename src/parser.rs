@@ -1,18 +1,45 @@
 use std::fs::File;
 use std::io::{BufReader, BufRead};
-use std::str::FromStr;
 use std::io;
+use std::sync::Arc;
+use crate::lexer::{self, tokenize, Token, TokenKind};
+use crate::ram::dialect::Dialect;
+use crate::ram::expression::{self, Expression, Lowering};
 use crate::ram::instruction::Instruction;
 use crate::ram::instruction_op::InstructionOp;
 use crate::ram::op::Op;
+use crate::ram::operand::Operand;
 use crate::ram::machine::RamMachine;
+use crate::ram::program::Program;
 use crate::ram::rel::Rel;
-use crate::ram::types::Number;
+use crate::ram::types::{Index, Label, Number};
 
-pub struct Parser;
+// the dialect decides which instruction forms are accepted, the tape functions do not need one
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Parser {
+    dialect: Dialect,
+}
 
 impl Parser {
-    pub fn parse_file(file_path: &str) -> Result<RamMachine, String> {
+    pub fn new() -> Self {
+        Parser::default()
+    }
+
+    pub fn with_dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    pub fn parse_file(&self, file_path: &str) -> Result<RamMachine, String> {
+        self.parse_program_file(file_path).map(|program| RamMachine::from_program(Arc::new(program)))
+    }
+
+    pub fn parse_str(&self, input: &str) -> Result<RamMachine, String> {
+        self.parse_program(input).map(|program| RamMachine::from_program(Arc::new(program)))
+    }
+
+    // only the program, for running it on many inputs without parsing it again
+    pub fn parse_program_file(&self, file_path: &str) -> Result<Program, String> {
         let file = File::open(file_path).map_err(|e| e.to_string())?;
         let reader = BufReader::new(file);
 
@@ -22,249 +49,385 @@ impl Parser {
             .map_err(|e| e.to_string())?;
 
         let line_refs: Vec<&str> = lines.iter().map(|s| s.as_str()).collect();
-        Self::parse_lines(line_refs)
+        self.parse_lines(line_refs)
     }
 
-    pub fn parse_str(input: &str) -> Result<RamMachine, String> {
+    pub fn parse_program(&self, input: &str) -> Result<Program, String> {
         let lines: Vec<&str> = input.lines().collect();
-        Self::parse_lines(lines)
+        self.parse_lines(lines)
     }
     
-    fn parse_lines(lines: Vec<&str>) -> Result<RamMachine, String> {
-        let mut instructions = Vec::new();
-        
-        for line in lines {
-            let line = line.trim().to_string();
-
-            let (line, comment) = line.split_once('#').unwrap_or((line.as_str(), ""));
-            
-            let mut instruction = Self::parse_instruction(line)
-                .ok_or_else(|| format!("Invalid instruction! {}", line))?;
-            
-            if !comment.is_empty() {
-                instruction = instruction.with_comment(comment);
-            }
+    pub fn parse_tape(input: &str) -> Result<Vec<Number>, String> {
+        // values are separated by commas, whitespace or newlines
+        if input.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+
+        if input.trim().ends_with(',') {
+            return Err("Input cannot end with a comma.".to_string());
+        }
 
-            instructions.push(instruction);
+        let mut tape = Vec::new();
+        for part in input.split(',') {
+            if part.trim().is_empty() {
+                return Err("Invalid input.".to_string());
+            }
+            for value in part.split_whitespace() {
+                tape.push(Self::parse_tape_value(value)?);
+            }
         }
+        Ok(tape)
+    }
 
-        Ok(RamMachine::new(instructions))
+    pub fn parse_tape_file(file_path: &str) -> Result<Vec<Number>, String> {
+        let contents = std::fs::read_to_string(file_path).map_err(|e| e.to_string())?;
+        Self::parse_tape(&contents)
     }
 
-    fn split_line(line: &str) -> (Option<&str>, &str) {
-        // we have to split by : but not by :=
-        if let Some(colon_pos) = line.find(':') {
-            if colon_pos != line.len() - 1 && !line[colon_pos + 1..].trim_start().starts_with('=') {
-                let (label_part, rest_part) = line.split_at(colon_pos);
-                let label = label_part.trim();
-                let right_side = rest_part[1..].trim();
-                return (Some(label), right_side);
+    pub fn parse_input_directive(source: &str) -> Option<Result<Vec<Number>, String>> {
+        // `# input: 1, 2, 3` among the comments at the top of the file, any comment style works
+        for line in source.lines().map(str::trim) {
+            if line.is_empty() {
+                continue;
+            }
+            let comment = ["#", ";", "//"].iter().find_map(|marker| line.strip_prefix(marker))?.trim();
+            if let Some(tape) = comment.strip_prefix("input:") {
+                return Some(Self::parse_tape(tape));
             }
         }
+        None
+    }
 
-        (None, line.trim())
+    fn parse_tape_value(input: &str) -> Result<Number, String> {
+        input.parse::<Number>().map_err(|_| {
+            if input.parse::<i64>().is_ok() {
+                format!(
+                    "Input value {input} is too {}.",
+                    if input.starts_with('-') { "small" } else { "large" }
+                )
+            } else {
+                "Invalid input.".to_string()
+            }
+        })
     }
 
-    fn parse_instruction(line: &str) -> Option<Instruction> {
-        let (label, operation) = Self::split_line(line);
+    // every line that fails to parse, with its zero-based line number
+    pub fn line_errors(&self, input: &str) -> Vec<(Index, String)> {
+        input.lines()
+            .enumerate()
+            .filter_map(|(i, line)| self.parse_line(line).err().map(|err| (i, err)))
+            .collect()
+    }
 
-        let mut instruction = Self::parse_assign_from_const(operation)
-            .or_else(|| Self::parse_assign_from_register(operation))
-            .or_else(|| Self::parse_load(operation))
-            .or_else(|| Self::parse_store(operation))
-            .or_else(|| Self::parse_arithmetic_op(operation))
-            .or_else(|| Self::parse_jump(operation))
-            .or_else(|| Self::parse_cond_jump_rel(operation))
-            .or_else(|| Self::parse_halt(operation))
-            .or_else(|| Self::parse_read(operation))
-            .or_else(|| Self::parse_write(operation))
-            .or_else(|| Self::parse_empty(operation))?;
+    fn parse_lines(&self, lines: Vec<&str>) -> Result<Program, String> {
+        let mut instructions: Vec<Instruction> = Vec::new();
+        let mut line_numbers = Vec::new();
+        // label-only lines waiting for the next real instruction
+        let mut pending = Vec::new();
 
-        if let Some(label) = label {
-            instruction = instruction.with_label(label);
+        for (i, line) in lines.into_iter().enumerate() {
+            let mut parsed = self.parse_line(line)
+                .map_err(|err| format!("Line {}: {}", i + 1, err))?;
+            let first = &mut parsed[0];
+            if first.op == InstructionOp::Empty {
+                if !first.labels.is_empty() {
+                    pending.push(instructions.len());
+                }
+            } else {
+                let mut labels: Vec<Label> = pending.drain(..)
+                    .flat_map(|index: Index| std::mem::take(&mut instructions[index].labels))
+                    .collect();
+                labels.append(&mut first.labels);
+                first.labels = labels;
+            }
+            line_numbers.extend(std::iter::repeat_n(i, parsed.len()));
+            instructions.extend(parsed);
         }
+        // labels at the very end stay where they are, jumping there ends the program
 
-        Some(instruction)
+        Ok(Program::new(instructions).with_lines(line_numbers))
     }
 
-    fn parse_op(input: &str) -> Option<Op> {
-        match input.trim() {
-            "+" => Some(Op::Add),
-            "-" => Some(Op::Sub),
-            "*" => Some(Op::Mul),
-            "/" => Some(Op::Div),
-            _ => None,
+    // one instruction, or several for a compound expression with the labels and comment on the first
+    fn parse_line(&self, line: &str) -> Result<Vec<Instruction>, String> {
+        let instructions = LineParser::new(line).parse()?;
+        if self.dialect == Dialect::Textbook {
+            if let Some(source) = &instructions[0].source {
+                return Err(format!("'{}' is not a textbook instruction", source));
+            }
+            if !self.dialect.allows(&instructions[0].op) {
+                return Err(format!("'{}' is not a textbook instruction", instructions[0].op));
+            }
         }
+        Ok(instructions)
     }
+}
 
-    fn parse_rel(input: &str) -> Option<Rel> {
-        match input.trim() {
-            "<" => Some(Rel::Lt),
-            ">" => Some(Rel::Gt),
-            "<=" => Some(Rel::Le),
-            ">=" => Some(Rel::Ge),
-            "==" => Some(Rel::Eq),
-            "!=" => Some(Rel::Ne),
-            _ => None,
+// recursive descent over the tokens of a single line:
+//
+//   line        := {label ':'} [instruction] [comment]
+//   label       := word {word}
+//   instruction := target ':=' value
+//                | 'goto' label
+//                | 'if' '(' expression relation expression ')' 'goto' label
+//                | 'halt'
+//                | 'write' '(' operand ')'
+//   value       := 'read' '(' ')' | expression
+//   expression  := term {('+' | '-') term}
+//   term        := factor {('*' | '/') factor}
+//   factor      := operand | '(' expression ')'
+//   target      := register | '[' register ']'
+//   operand     := target | constant
+//
+// compound expressions are lowered to several instructions by `Lowering`
+struct LineParser<'a> {
+    line: &'a str,
+    tokens: Vec<Token<'a>>,
+    comment: Option<&'a str>,
+    position: usize,
+}
+
+impl<'a> LineParser<'a> {
+    fn new(line: &'a str) -> LineParser<'a> {
+        let mut tokens = Vec::new();
+        let mut comment = None;
+        for token in tokenize(line) {
+            match token.kind {
+                TokenKind::Whitespace => {}
+                TokenKind::Comment => comment = Some(token.text),
+                _ => tokens.push(token),
+            }
         }
+        LineParser { line, tokens, comment, position: 0 }
     }
 
-    fn parse_register(input: &str) -> Option<Number> {
-        // R3, R6, R129, ...
-        let stripped = input.strip_prefix('R')?;
-        i32::from_str(stripped).ok()
+    fn parse(mut self) -> Result<Vec<Instruction>, String> {
+        let mut labels = Vec::new();
+        while self.label_length().is_some_and(|length| self.peek_kind(length) == Some(TokenKind::Colon)) {
+            labels.push(self.label()?);
+            self.next();
+        }
+
+        let Some(start) = self.peek().map(|token| token.start) else {
+            return Ok(vec![self.finish(Instruction::new(InstructionOp::Empty), labels)]);
+        };
+        let mut ops = self.instruction()?;
+        if let Some(token) = self.peek() {
+            return Err(format!("Unexpected '{}' at column {}", token.text, token.start + 1));
+        }
+
+        let first = self.finish(Instruction::new(ops.remove(0)), labels);
+        if ops.is_empty() {
+            return Ok(vec![first]);
+        }
+        // every instruction of a lowered expression remembers where it came from
+        let end = self.tokens.last().map_or(start, |token| token.start + token.text.len());
+        let source = &self.line[start..end];
+        Ok(std::iter::once(first).chain(ops.into_iter().map(Instruction::new))
+            .map(|instruction| instruction.with_source(source))
+            .collect())
+    }
+
+    fn finish(&self, mut instruction: Instruction, labels: Vec<Label>) -> Instruction {
+        for label in labels {
+            instruction = instruction.with_label(&label);
+        }
+        if let Some(comment) = self.comment {
+            let text = ["#", ";", "//"].iter()
+                .find_map(|marker| comment.strip_prefix(marker))
+                .unwrap_or(comment);
+            instruction = instruction.with_comment(text);
+        }
+        instruction
     }
 
-    fn parse_value(input: &str) -> Option<Number> {
-        // 1, 3, -7, 7734, ...
-        i32::from_str(input.trim()).ok()
+    fn instruction(&mut self) -> Result<Vec<InstructionOp>, String> {
+        if self.accept_keyword("goto") {
+            return Ok(vec![InstructionOp::Jump(self.label()?)]);
+        }
+        if self.accept_keyword("halt") {
+            return Ok(vec![InstructionOp::Halt]);
+        }
+        if self.accept_keyword("write") {
+            self.expect(TokenKind::LeftParen, "'('")?;
+            let value = self.operand()?;
+            self.expect(TokenKind::RightParen, "')'")?;
+            return Ok(vec![InstructionOp::Write(value)]);
+        }
+        if self.accept_keyword("if") {
+            return self.cond_jump();
+        }
+
+        let target = self.target()?;
+        self.expect(TokenKind::Assign, "':='")?;
+        if self.accept_keyword("read") {
+            self.expect(TokenKind::LeftParen, "'('")?;
+            self.expect(TokenKind::RightParen, "')'")?;
+            return Ok(vec![InstructionOp::Read(target)]);
+        }
+        Lowering::assign(target, self.expression()?)
     }
 
-    fn parse_memory_access(input: &str) -> Option<Number> {
-        // [R1], [R123], ...
-        let input = input.strip_prefix('[')?.strip_suffix(']')?;
-        Self::parse_register(input)
+    fn expression(&mut self) -> Result<Expression, String> {
+        let mut expression = self.term()?;
+        while let Some(op) = self.accept_operator(&["+", "-"]) {
+            expression = Expression::binary(expression, op, self.term()?);
+        }
+        Ok(expression)
     }
 
-    fn parse_assign_from_const(line: &str) -> Option<Instruction> {
-        // Ri ∶= c
-        let parts: Vec<&str> = line.split(":=").map(str::trim).collect();
-        if parts.len() == 2 {
-            let target = Self::parse_register(parts[0])?;
-            let value = Self::parse_value(parts[1])?;
-            return Some(Instruction::new(InstructionOp::AssignFromConst(target, value)));
+    fn term(&mut self) -> Result<Expression, String> {
+        let mut expression = self.factor()?;
+        while let Some(op) = self.accept_operator(&["*", "/"]) {
+            expression = Expression::binary(expression, op, self.factor()?);
         }
-        None
+        Ok(expression)
     }
 
-    fn parse_assign_from_register(line: &str) -> Option<Instruction> {
-        // Ri ∶= Rj
-        let parts: Vec<&str> = line.split(":=").map(str::trim).collect();
-        if parts.len() == 2 {
-            let target = Self::parse_register(parts[0])?;
-            let source = Self::parse_register(parts[1])?;
-            return Some(Instruction::new(InstructionOp::AssignFromRegister(target, source)));
+    fn factor(&mut self) -> Result<Expression, String> {
+        if self.accept(TokenKind::LeftParen).is_some() {
+            let expression = self.expression()?;
+            self.expect(TokenKind::RightParen, "')'")?;
+            return Ok(expression);
         }
-        None
+        Ok(Expression::Operand(self.operand()?))
     }
 
-    fn parse_load(line: &str) -> Option<Instruction> {
-        // Ri ∶= [Rj]
-        let parts: Vec<&str> = line.split(":=").map(str::trim).collect();
-        if parts.len() == 2 {
-            let target = Self::parse_register(parts[0])?;
-            let source = Self::parse_memory_access(parts[1])?;
-            return Some(Instruction::new(InstructionOp::Load(target, source)));
+    fn accept_operator(&mut self, operators: &[&str]) -> Option<Op> {
+        let token = self.peek().filter(|token| token.kind == TokenKind::Operator && operators.contains(&token.text))?;
+        self.next();
+        Some(match token.text {
+            "+" => Op::Add,
+            "-" => Op::Sub,
+            "*" => Op::Mul,
+            _ => Op::Div,
+        })
+    }
+
+    fn cond_jump(&mut self) -> Result<Vec<InstructionOp>, String> {
+        self.expect(TokenKind::LeftParen, "'('")?;
+        let left = self.expression()?;
+        let relation = self.expect(TokenKind::Relation, "a comparison")?;
+        let rel = match relation.text {
+            "<" => Rel::Lt,
+            ">" => Rel::Gt,
+            "<=" | "≤" => Rel::Le,
+            ">=" | "≥" => Rel::Ge,
+            "==" => Rel::Eq,
+            _ => Rel::Ne,
+        };
+        let right = self.expression()?;
+        self.expect(TokenKind::RightParen, "')'")?;
+        if !self.accept_keyword("goto") {
+            return Err(self.unexpected("'goto'"));
         }
-        None
+        let label = self.label()?;
+
+        Lowering::cond_jump(left, rel, right, label)
     }
 
-    fn parse_store(line: &str) -> Option<Instruction> {
-        // [Ri] ∶= Rj
-        let parts: Vec<&str> = line.split(":=").map(str::trim).collect();
-        if parts.len() == 2 {
-            let target = Self::parse_memory_access(parts[0])?;
-            let source = Self::parse_register(parts[1])?;
-            return Some(Instruction::new(InstructionOp::Store(target, source)));
+    // Ri or [Ri], anything that can be assigned to
+    fn target(&mut self) -> Result<Operand, String> {
+        if self.accept(TokenKind::LeftBracket).is_some() {
+            let register = self.register()?;
+            self.expect(TokenKind::RightBracket, "']'")?;
+            return Ok(Operand::Indirect(register));
         }
-        None
+        Ok(Operand::Register(self.register()?))
     }
 
-    fn parse_arithmetic_op(line: &str) -> Option<Instruction> {
-        // Ri ∶= Rj op Rk
-        // Ri ∶= Rj op c
-        let parts: Vec<&str> = line.split(":=").map(str::trim).collect();
-        if parts.len() == 2 {
-            let target = Self::parse_register(parts[0])?;
-            let tokens: Vec<&str> = parts[1].split_whitespace().collect();
-            if tokens.len() == 3 {
-                let source1 = Self::parse_register(tokens[0])?;
-                let op = Self::parse_op(tokens[1])?;
-                if let Some(source2) = Self::parse_register(tokens[2]) {
-                    return Some(Instruction::new(
-                        InstructionOp::ArithmeticRegOpReg(target, source1, op, source2)));
-                }
-                if let Some(value) = Self::parse_value(tokens[2]) {
-                    return Some(Instruction::new(
-                        InstructionOp::ArithmeticRegOpConst(target, source1, op, value)));
-                }
-            }
+    fn operand(&mut self) -> Result<Operand, String> {
+        match self.peek_kind(0) {
+            Some(TokenKind::Register | TokenKind::LeftBracket) => self.target(),
+            _ => Ok(Operand::Constant(self.constant()?)),
         }
-        None
     }
 
-    fn parse_jump(line: &str) -> Option<Instruction> {
-        // goto ℓ (probably going to allow multiword labels)
-        if line.starts_with("goto") {
-            let label = line.strip_prefix("goto")?.trim();
-            return Some(Instruction::new(InstructionOp::Jump(label.to_string())));
+    fn register(&mut self) -> Result<Number, String> {
+        let token = self.expect(TokenKind::Register, "a register")?;
+        let reg = token.text[1..].parse::<Number>()
+            .map_err(|_| format!("Register {} is out of range", token.text))?;
+        if expression::is_temporary(reg) {
+            return Err(format!("Registers R-1 to R-{} are reserved for the temporaries of expressions", expression::TEMPORARIES));
         }
-        None
+        Ok(reg)
     }
 
-    fn parse_cond_jump_rel(line: &str) -> Option<Instruction> {
-        // if (Ri rel Rj) goto ℓ
-        // if (Ri rel c) goto ℓ
-        if line.starts_with("if") && line.contains("goto") {
-            let parts: Vec<&str> = line.strip_prefix("if")?.trim()
-                .split("goto").map(str::trim).collect();
-            if parts.len() == 2 {
-                let tokens: Vec<&str> = parts[0].strip_prefix('(')?.strip_suffix(')')?
-                    .split_whitespace().collect();
-                if tokens.len() == 3 {
-                    let reg1 = Self::parse_register(tokens[0])?;
-                    let rel = Self::parse_rel(tokens[1])?;
-                    let label = parts[1];
-                    if let Some(value) = Self::parse_value(tokens[2]) {
-                        return Some(Instruction::new(
-                            InstructionOp::CondJumpRegRelConst(reg1, rel, value, label.to_string())))
-                    }
-                    if let Some(reg2) = Self::parse_register(tokens[2]) {
-                        return Some(Instruction::new(
-                            InstructionOp::CondJumpRegRelReg(reg1, rel, reg2, label.to_string())))
-                    }
-                }
+    fn constant(&mut self) -> Result<Number, String> {
+        // a sign is a separate operator token
+        let sign = match self.peek() {
+            Some(token) if token.kind == TokenKind::Operator && matches!(token.text, "-" | "+") => {
+                self.next();
+                token.text
             }
+            _ => "",
+        };
+        let digits = self.expect(TokenKind::Number, "a register or a number")?.text;
+        let text = if sign == "-" { format!("-{}", digits) } else { digits.to_string() };
+        text.parse::<Number>().map_err(|_| format!("Number {} is out of range", text))
+    }
+
+    // labels can have several words, they are joined with single spaces
+    // the words keep their characters, whitespace between them becomes a single space
+    fn label(&mut self) -> Result<Label, String> {
+        let length = self.label_length().ok_or_else(|| self.unexpected("a label"))?;
+        let mut label = String::new();
+        let mut end = None;
+        for token in (0..length).filter_map(|_| self.next()) {
+            if end.is_some_and(|end| end < token.start) {
+                label.push(' ');
+            }
+            label.push_str(token.text);
+            end = Some(token.start + token.text.len());
         }
-        None
+        Ok(label)
     }
 
-    fn parse_halt(line: &str) -> Option<Instruction> {
-        // halt
-        if line.trim() == "halt" {
-            return Some(Instruction::new(InstructionOp::Halt));
+    // number of tokens of the label starting at the current position
+    fn label_length(&self) -> Option<usize> {
+        let length = lexer::label_length(&self.tokens[self.position.min(self.tokens.len())..]);
+        (length > 0).then_some(length)
+    }
+
+    fn peek(&self) -> Option<Token<'a>> {
+        self.tokens.get(self.position).copied()
+    }
+
+    fn peek_kind(&self, offset: usize) -> Option<TokenKind> {
+        self.tokens.get(self.position + offset).map(|token| token.kind)
+    }
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        let token = self.peek();
+        self.position += 1;
+        token
+    }
+
+    fn accept(&mut self, kind: TokenKind) -> Option<Token<'a>> {
+        match self.peek() {
+            Some(token) if token.kind == kind => self.next(),
+            _ => None,
         }
-        None
     }
 
-    fn parse_read(line: &str) -> Option<Instruction> {
-        // Ri := read()
-        let parts: Vec<&str> = line.split(":=").map(str::trim).collect();
-        if parts.len() == 2 {
-            let inside = parts[1].strip_prefix("read")?.trim()
-                .strip_prefix('(')?.trim()
-                .strip_suffix(')')?.trim();
-            if inside.is_empty() {
-                let register = Self::parse_register(parts[0])?;
-                return Some(Instruction::new(InstructionOp::Read(register)));
+    fn accept_keyword(&mut self, keyword: &str) -> bool {
+        match self.peek() {
+            Some(token) if token.kind == TokenKind::Keyword && token.text == keyword => {
+                self.next();
+                true
             }
+            _ => false,
         }
-        None
     }
 
-    fn parse_write(line: &str) -> Option<Instruction> {
-        // write(Ri)
-        let inside = line.strip_prefix("write")?.trim()
-            .strip_prefix('(')?.trim()
-            .strip_suffix(')')?.trim();
-        let register = Self::parse_register(inside)?;
-        Some(Instruction::new(InstructionOp::Write(register)))
+    fn expect(&mut self, kind: TokenKind, expected: &str) -> Result<Token<'a>, String> {
+        self.accept(kind).ok_or_else(|| self.unexpected(expected))
     }
-    
-    fn parse_empty(line: &str) -> Option<Instruction> {
-        if line.is_empty() {
-            return Some(Instruction::new(InstructionOp::Empty));
+
+    fn unexpected(&self, expected: &str) -> String {
+        match self.peek() {
+            Some(token) => format!("Expected {} at column {}, found '{}'", expected, token.start + 1, token.text),
+            None => format!("Expected {} at the end of the line", expected),
         }
-        None
     }
 }
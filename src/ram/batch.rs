@@ -0,0 +1,96 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use crate::ram::compiled::{CompiledMachine, CompiledProgram};
+use crate::ram::program::Program;
+use crate::ram::run::RunOutcome;
+use crate::ram::types::{Index, Number};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchOptions {
+    step_limit: Option<Index>,
+    threads: usize,
+}
+
+impl Default for BatchOptions {
+    fn default() -> Self {
+        BatchOptions {
+            step_limit: None,
+            threads: thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+        }
+    }
+}
+
+impl BatchOptions {
+    pub fn new() -> Self {
+        BatchOptions::default()
+    }
+
+    // applies to every input separately
+    pub fn with_step_limit(mut self, limit: Index) -> Self {
+        self.step_limit = Some(limit);
+        self
+    }
+
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchResult {
+    pub outcome: RunOutcome,
+    pub output: Vec<Number>,
+    pub steps: Index,
+}
+
+impl BatchResult {
+    pub fn error(&self) -> Option<String> {
+        match &self.outcome {
+            RunOutcome::Error(message) => Some(message.clone()),
+            RunOutcome::LimitExceeded => Some(format!("Step limit exceeded after {} steps", self.steps)),
+            RunOutcome::AwaitingInput => Some("Attempt to read from empty tape".to_string()),
+            _ => None,
+        }
+    }
+}
+
+// runs the program on every input, the results are in the order of the inputs
+pub fn run_batch(program: Arc<Program>, inputs: &[Vec<Number>], options: BatchOptions) -> Vec<BatchResult> {
+    let program = Arc::new(CompiledProgram::compile(&program));
+    let next = AtomicUsize::new(0);
+    let threads = options.threads.min(inputs.len()).max(1);
+
+    let mut finished: Vec<(usize, BatchResult)> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| scope.spawn(|| {
+                let mut results = Vec::new();
+                // every worker takes the next input until none are left
+                loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(input) = inputs.get(i) else {
+                        return results;
+                    };
+                    results.push((i, run_single(&program, input.clone(), options.step_limit)));
+                }
+            }))
+            .collect();
+        workers.into_iter()
+            .flat_map(|worker| worker.join().expect("batch worker panicked"))
+            .collect()
+    });
+
+    finished.sort_by_key(|(i, _)| *i);
+    finished.into_iter().map(|(_, result)| result).collect()
+}
+
+fn run_single(program: &Arc<CompiledProgram>, input: Vec<Number>, step_limit: Option<Index>) -> BatchResult {
+    let mut machine = CompiledMachine::new(Arc::clone(program), input);
+    let outcome = machine.run(step_limit);
+    BatchResult {
+        outcome,
+        output: machine.get_output().clone(),
+        steps: machine.get_steps(),
+    }
+}
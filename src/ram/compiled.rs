@@ -0,0 +1,260 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use crate::ram::expression::{self, TEMPORARIES};
+use crate::ram::instruction_op::InstructionOp;
+use crate::ram::op::Op;
+use crate::ram::operand::Operand;
+use crate::ram::program::Program;
+use crate::ram::rel::Rel;
+use crate::ram::run::RunOutcome;
+use crate::ram::types::{Index, Label, Number};
+
+// cells below this address live in a vector, the rest in a map
+const DENSE_LIMIT: Number = 1 << 16;
+
+// jump target of a label that does not exist, jumping there fails like `step` does
+const MISSING: Index = Index::MAX;
+
+// an instruction with its jump targets resolved to the next non-empty instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Code {
+    Assign(Operand, Operand),
+    Arithmetic(Operand, Operand, Op, Operand),
+    Jump(Index),
+    CondJump(Operand, Rel, Operand, Index),
+    Read(Operand),
+    Write(Operand),
+    Halt,
+    Empty,
+}
+
+// bytecode for the plain RAM mode, runs the same as `RamMachine::step` without the bookkeeping
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompiledProgram {
+    code: Vec<Code>,
+    // where execution continues after the instruction, empty lines skipped
+    next: Vec<Index>,
+    // labels of jumps that point nowhere, for the error message
+    missing: HashMap<Index, Label>,
+    start: Index,
+}
+
+impl CompiledProgram {
+    pub fn compile(program: &Program) -> CompiledProgram {
+        let instructions = program.instructions();
+        let len = instructions.len();
+
+        // first non-empty instruction at or after every index
+        let mut skip = vec![len; len + 1];
+        for i in (0..len).rev() {
+            skip[i] = if instructions[i].op == InstructionOp::Empty { skip[i + 1] } else { i };
+        }
+
+        let mut missing = HashMap::new();
+        let mut target = |index: Index, label: &Label| match program.label(label) {
+            Some(target) => skip[target],
+            None => {
+                missing.insert(index, label.clone());
+                MISSING
+            }
+        };
+
+        let code = instructions.iter().enumerate()
+            .map(|(i, instruction)| match &instruction.op {
+                InstructionOp::Assign(t, v) => Code::Assign(*t, *v),
+                InstructionOp::Arithmetic(t, a, op, b) => Code::Arithmetic(*t, *a, *op, *b),
+                InstructionOp::Jump(label) => Code::Jump(target(i, label)),
+                InstructionOp::CondJump(a, rel, b, label) => Code::CondJump(*a, *rel, *b, target(i, label)),
+                InstructionOp::Read(t) => Code::Read(*t),
+                InstructionOp::Write(v) => Code::Write(*v),
+                InstructionOp::Halt => Code::Halt,
+                InstructionOp::Empty => Code::Empty,
+            })
+            .collect();
+
+        CompiledProgram {
+            code,
+            next: (0..len).map(|i| skip[i + 1]).collect(),
+            missing,
+            start: skip[0],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.code.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.code.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Memory {
+    dense: Vec<Number>,
+    sparse: HashMap<Number, Number>,
+    temporaries: [Number; TEMPORARIES as usize],
+}
+
+impl Memory {
+    fn get(&self, address: Number) -> Number {
+        match self.dense.get(address as usize) {
+            Some(&value) if address >= 0 => value,
+            _ => self.sparse.get(&address).copied().unwrap_or(0),
+        }
+    }
+
+    fn set(&mut self, address: Number, value: Number) {
+        if (0..DENSE_LIMIT).contains(&address) {
+            let index = address as usize;
+            if index >= self.dense.len() {
+                self.dense.resize(index + 1, 0);
+            }
+            self.dense[index] = value;
+        } else {
+            self.sparse.insert(address, value);
+        }
+    }
+
+    fn value(&self, operand: Operand) -> Number {
+        match operand {
+            Operand::Constant(value) => value,
+            Operand::Register(reg) if expression::is_temporary(reg) => self.temporaries[expression::slot(reg)],
+            Operand::Register(reg) => self.get(reg),
+            Operand::Indirect(reg) => self.get(self.get(reg)),
+        }
+    }
+
+    fn assign(&mut self, target: Operand, value: Number) -> Result<(), String> {
+        let address = match target {
+            Operand::Constant(constant) => return Err(format!("Cannot assign to the constant {}", constant)),
+            Operand::Register(reg) if expression::is_temporary(reg) => {
+                self.temporaries[expression::slot(reg)] = value;
+                return Ok(());
+            }
+            Operand::Register(reg) => reg,
+            Operand::Indirect(reg) => self.get(reg),
+        };
+        self.set(address, value);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CompiledMachine {
+    program: Arc<CompiledProgram>,
+    memory: Memory,
+    instruction_pointer: Index,
+    input_tape: Vec<Number>,
+    input_pointer: Index,
+    output_tape: Vec<Number>,
+    steps: Index,
+}
+
+impl CompiledMachine {
+    pub fn new(program: Arc<CompiledProgram>, input_tape: Vec<Number>) -> CompiledMachine {
+        CompiledMachine {
+            instruction_pointer: program.start,
+            program,
+            memory: Memory::default(),
+            input_tape,
+            input_pointer: 0,
+            output_tape: Vec::new(),
+            steps: 0,
+        }
+    }
+
+    pub fn get(&self, reg: Number) -> Number {
+        self.memory.get(reg)
+    }
+
+    // every cell holding a value other than zero
+    pub fn get_cells(&self) -> HashMap<Number, Number> {
+        let dense = self.memory.dense.iter().enumerate()
+            .map(|(address, &value)| (address as Number, value));
+        dense.chain(self.memory.sparse.iter().map(|(&address, &value)| (address, value)))
+            .filter(|(_, value)| *value != 0)
+            .collect()
+    }
+
+    pub fn get_output(&self) -> &Vec<Number> {
+        &self.output_tape
+    }
+
+    pub fn get_input_pointer(&self) -> Index {
+        self.input_pointer
+    }
+
+    pub fn get_instruction_pointer(&self) -> Index {
+        self.instruction_pointer
+    }
+
+    // instructions executed successfully, the same as `RunStats::steps`
+    pub fn get_steps(&self) -> Index {
+        self.steps
+    }
+
+    // runs until the program stops or `step_limit` more instructions have been executed
+    pub fn run(&mut self, step_limit: Option<Index>) -> RunOutcome {
+        let limit = step_limit.map_or(Index::MAX, |limit| self.steps.saturating_add(limit));
+        let program = Arc::clone(&self.program);
+        let len = program.code.len();
+
+        loop {
+            let ip = self.instruction_pointer;
+            if ip >= len {
+                return RunOutcome::FellOffEnd;
+            }
+            if self.steps >= limit {
+                return RunOutcome::LimitExceeded;
+            }
+
+            let target = match program.code[ip] {
+                Code::Assign(t, v) => {
+                    if let Err(err) = self.memory.assign(t, self.memory.value(v)) {
+                        return RunOutcome::Error(err);
+                    }
+                    None
+                }
+                Code::Arithmetic(t, a, op, b) => {
+                    let result = op.apply(self.memory.value(a), self.memory.value(b))
+                        .and_then(|value| self.memory.assign(t, value));
+                    if let Err(err) = result {
+                        return RunOutcome::Error(err);
+                    }
+                    None
+                }
+                Code::Jump(target) => Some(target),
+                Code::CondJump(a, rel, b, target) => {
+                    rel.holds(self.memory.value(a), self.memory.value(b)).then_some(target)
+                }
+                Code::Halt => {
+                    self.steps += 1;
+                    return RunOutcome::Halted;
+                }
+                Code::Read(t) => {
+                    let Some(&value) = self.input_tape.get(self.input_pointer) else {
+                        return RunOutcome::Error("Attempt to read from empty tape".to_string());
+                    };
+                    if let Err(err) = self.memory.assign(t, value) {
+                        return RunOutcome::Error(err);
+                    }
+                    self.input_pointer += 1;
+                    None
+                }
+                Code::Write(v) => {
+                    self.output_tape.push(self.memory.value(v));
+                    None
+                }
+                Code::Empty => return RunOutcome::Error("Empty instruction should be skipped".to_string()),
+            };
+
+            match target {
+                Some(MISSING) => return RunOutcome::Error(format!("Label {} not found", program.missing[&ip])),
+                Some(target) => self.instruction_pointer = target,
+                None => self.instruction_pointer = program.next[ip],
+            }
+            self.steps += 1;
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use std::collections::HashMap;
+use crate::ram::types::{Index, Number};
+
+// everything the next steps depend on, the output tape only grows and is left out
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct State {
+    instruction_pointer: Index,
+    input_pointer: Index,
+    memory: Vec<(Number, Number)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub first_step: Index,
+    pub length: Index,
+    pub instruction: Index,
+}
+
+// remembers every state the machine was in, a repeated state means it loops forever
+#[derive(Debug, Clone, Default)]
+pub struct LoopDetector {
+    seen: HashMap<State, Index>,
+}
+
+impl LoopDetector {
+    pub fn new() -> LoopDetector {
+        LoopDetector::default()
+    }
+
+    pub fn check(
+        &mut self,
+        step: Index,
+        instruction_pointer: Index,
+        input_pointer: Index,
+        memory: &HashMap<Number, Number>,
+    ) -> Option<Cycle> {
+        // unset cells read as zero, so zeros are left out to compare equal
+        let mut cells: Vec<(Number, Number)> = memory.iter()
+            .filter(|(_, &value)| value != 0)
+            .map(|(&address, &value)| (address, value))
+            .collect();
+        cells.sort_unstable();
+
+        let state = State { instruction_pointer, input_pointer, memory: cells };
+        match self.seen.get(&state) {
+            Some(&first_step) => Some(Cycle { first_step, length: step - first_step, instruction: instruction_pointer }),
+            None => {
+                self.seen.insert(state, step);
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use std::fmt::Display;
+use crate::ram::instruction_op::InstructionOp;
+use crate::ram::operand::Operand::{self, Constant, Indirect, Register};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Dialect {
+    // registers, constants and indirect accesses can be used anywhere a value is read
+    #[default]
+    Extended,
+    // only the instruction forms of the classic RAM definition
+    Textbook,
+}
+
+impl Dialect {
+    pub fn allows(self, op: &InstructionOp) -> bool {
+        self == Dialect::Extended || Self::is_textbook(op)
+    }
+
+    // Ri := c | Rj | [Rj], [Ri] := Rj, Ri := Rj op Rk | c, if (Ri rel Rj | c) goto ℓ, Ri := read(), write(Ri)
+    fn is_textbook(op: &InstructionOp) -> bool {
+        let is_register = |operand: &Operand| matches!(operand, Register(_));
+        let is_register_or_constant = |operand: &Operand| matches!(operand, Register(_) | Constant(_));
+        match op {
+            InstructionOp::Assign(Register(_), _) => true,
+            InstructionOp::Assign(Indirect(_), value) => is_register(value),
+            InstructionOp::Assign(Constant(_), _) => false,
+            InstructionOp::Arithmetic(target, a, _, b) => is_register(target) && is_register(a) && is_register_or_constant(b),
+            InstructionOp::CondJump(a, _, b, _) => is_register(a) && is_register_or_constant(b),
+            InstructionOp::Read(target) => is_register(target),
+            InstructionOp::Write(value) => is_register(value),
+            InstructionOp::Jump(_) | InstructionOp::Halt | InstructionOp::Empty => true,
+        }
+    }
+}
+
+impl Display for Dialect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Dialect::Extended => write!(f, "extended"),
+            Dialect::Textbook => write!(f, "textbook"),
+        }
+    }
+}
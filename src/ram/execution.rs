@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+use crate::ram::execution_mode::ExecutionMode;
+use crate::ram::expression::TEMPORARIES;
+use crate::ram::types::{Index, Number};
+
+// the state of a single run of a program, cheap to create for every input tape
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Execution {
+    pub(crate) memory: HashMap<Number, Number>,
+    pub(crate) instruction_pointer: Index,
+    pub(crate) input_pointer: Index,
+    pub(crate) input_tape: Vec<Number>,
+    pub(crate) output_tape: Vec<Number>,
+    pub(crate) instruction_count: Index,
+    pub(crate) changed: Vec<Number>,
+    pub(crate) temporaries: [Number; TEMPORARIES as usize],
+    // a RASP run keeps its program in memory, so the mode belongs to the state
+    pub(crate) mode: ExecutionMode,
+}
+
+impl Execution {
+    pub fn new(input_tape: Vec<Number>) -> Execution {
+        Execution {
+            input_tape,
+            ..Execution::default()
+        }
+    }
+
+    pub fn get(&self, reg: Number) -> Number {
+        self.memory.get(&reg).copied().unwrap_or(0)
+    }
+
+    pub fn get_memory(&self) -> &HashMap<Number, Number> {
+        &self.memory
+    }
+
+    pub fn get_instruction_pointer(&self) -> Index {
+        self.instruction_pointer
+    }
+
+    pub fn get_input_pointer(&self) -> Index {
+        self.input_pointer
+    }
+
+    pub fn get_input(&self) -> &Vec<Number> {
+        &self.input_tape
+    }
+
+    pub fn get_output(&self) -> &Vec<Number> {
+        &self.output_tape
+    }
+
+    pub fn get_instruction_count(&self) -> Index {
+        self.instruction_count
+    }
+
+    pub fn get_mode(&self) -> ExecutionMode {
+        self.mode
+    }
+}
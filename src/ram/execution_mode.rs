@@ -0,0 +1,20 @@
+use std::fmt::Display;
+use crate::ram::types::Number;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExecutionMode {
+    // program is read directly from the instruction list
+    #[default]
+    Ram,
+    // program is encoded into memory starting at the given address
+    Rasp(Number),
+}
+
+impl Display for ExecutionMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExecutionMode::Ram => write!(f, "RAM"),
+            ExecutionMode::Rasp(base) => write!(f, "RASP @ {}", base),
+        }
+    }
+}
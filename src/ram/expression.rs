@@ -0,0 +1,100 @@
+use crate::ram::instruction_op::InstructionOp;
+use crate::ram::op::Op;
+use crate::ram::operand::Operand;
+use crate::ram::rel::Rel;
+use crate::ram::types::{Label, Number};
+
+// R-1 down to R-16 hold the intermediate results of compound expressions,
+// programs cannot name them and they are kept apart from the memory
+pub const TEMPORARIES: Number = 16;
+
+pub fn is_temporary(reg: Number) -> bool {
+    (-TEMPORARIES..0).contains(&reg)
+}
+
+// slot of a temporary in `Execution::temporaries`
+pub fn slot(reg: Number) -> usize {
+    (-reg - 1) as usize
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+    Operand(Operand),
+    Binary(Box<Expression>, Op, Box<Expression>),
+}
+
+impl Expression {
+    pub fn binary(left: Expression, op: Op, right: Expression) -> Expression {
+        Expression::Binary(Box::new(left), op, Box::new(right))
+    }
+}
+
+// turns `R0 := (R1 + R2) * 3 - R4` into
+//
+//   R-1 := R1 + R2
+//   R-1 := R-1 * 3
+//   R0 := R-1 - R4
+//
+// temporaries are reused as soon as the instruction reading them is emitted
+#[derive(Default)]
+pub struct Lowering {
+    ops: Vec<InstructionOp>,
+    in_use: Number,
+}
+
+impl Lowering {
+    pub fn assign(target: Operand, expression: Expression) -> Result<Vec<InstructionOp>, String> {
+        let mut lowering = Lowering::default();
+        lowering.assign_to(target, expression)?;
+        Ok(lowering.ops)
+    }
+
+    pub fn cond_jump(left: Expression, rel: Rel, right: Expression, label: Label) -> Result<Vec<InstructionOp>, String> {
+        let mut lowering = Lowering::default();
+        let a = lowering.operand(left)?;
+        let b = lowering.operand(right)?;
+        lowering.ops.push(InstructionOp::CondJump(a, rel, b, label));
+        Ok(lowering.ops)
+    }
+
+    fn assign_to(&mut self, target: Operand, expression: Expression) -> Result<(), String> {
+        let (left, op, right) = match expression {
+            Expression::Operand(value) => {
+                self.ops.push(InstructionOp::Assign(target, value));
+                return Ok(());
+            }
+            Expression::Binary(left, op, right) => (*left, op, *right),
+        };
+
+        let in_use = self.in_use;
+        // a temporary target can hold the left side while the right one is computed,
+        // a register of the program cannot as the right side might still read it
+        let a = match (target, &left) {
+            (Operand::Register(reg), Expression::Binary(..)) if is_temporary(reg) => {
+                self.assign_to(target, left)?;
+                target
+            }
+            _ => self.operand(left)?,
+        };
+        let b = self.operand(right)?;
+        self.ops.push(InstructionOp::Arithmetic(target, a, op, b));
+        self.in_use = in_use;
+        Ok(())
+    }
+
+    // the expression as a single operand, computed into a temporary when needed
+    fn operand(&mut self, expression: Expression) -> Result<Operand, String> {
+        match expression {
+            Expression::Operand(value) => Ok(value),
+            binary => {
+                if self.in_use == TEMPORARIES {
+                    return Err(format!("Expression needs more than {} temporary registers", TEMPORARIES));
+                }
+                self.in_use += 1;
+                let temporary = Operand::Register(-self.in_use);
+                self.assign_to(temporary, binary)?;
+                Ok(temporary)
+            }
+        }
+    }
+}
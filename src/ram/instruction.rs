@@ -3,33 +3,40 @@ use crate::ram::types::Label;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Instruction {
-    pub label: Option<Label>,
+    // every label of the instruction, including those from label-only lines above it
+    pub labels: Vec<Label>,
     pub op: InstructionOp,
     pub comment: Option<String>,
+    // the source statement when it was lowered into several instructions
+    pub source: Option<String>,
 }
 
 impl Instruction {
     pub fn new(instruction: InstructionOp) -> Instruction {
         Instruction {
-            label: None,
+            labels: Vec::new(),
             op: instruction,
             comment: None,
+            source: None,
         }
     }
 
-    pub fn with_label(self, label: &str) -> Instruction {
-        Instruction {
-            label: Some(label.to_string()),
-            op: self.op,
-            comment: self.comment,
-        }
+    pub fn with_label(mut self, label: &str) -> Instruction {
+        self.labels.push(label.to_string());
+        self
     }
     
     pub fn with_comment(self, comment: &str) -> Instruction {
         Instruction {
-            label: self.label,
-            op: self.op,
             comment: Some(comment.to_string()),
+            ..self
+        }
+    }
+
+    pub fn with_source(self, source: &str) -> Instruction {
+        Instruction {
+            source: Some(source.to_string()),
+            ..self
         }
     }
 }
\ No newline at end of file
@@ -1,54 +1,56 @@
 use std::fmt::Display;
 use crate::ram::op::Op;
+use crate::ram::operand::Operand;
 use crate::ram::rel::Rel;
-use crate::ram::types::Number;
+use crate::ram::types::Label;
 
+// targets are registers or indirect accesses, values can also be constants
 #[derive(Debug, Clone, PartialEq)]
 pub enum InstructionOp {
-    AssignFromConst(Number, Number),
-    AssignFromRegister(Number, Number),
-    Load(Number, Number),
-    Store(Number, Number),
-    ArithmeticRegOpReg(Number, Number, Op, Number),
-    ArithmeticRegOpConst(Number, Number, Op, Number),
+    // target := value
+    Assign(Operand, Operand),
+    // target := value op value
+    Arithmetic(Operand, Operand, Op, Operand),
     Jump(String),
-    CondJumpRegRelReg(Number, Rel, Number, String),
-    CondJumpRegRelConst(Number, Rel, Number, String),
-    Read(Number),
-    Write(Number),
+    // if (value rel value) goto ℓ
+    CondJump(Operand, Rel, Operand, String),
+    // target := read()
+    Read(Operand),
+    // write(value)
+    Write(Operand),
     Halt,
     Empty,
 }
 
+impl InstructionOp {
+    // label a jump goes to
+    pub fn target_label(&self) -> Option<&Label> {
+        match self {
+            InstructionOp::Jump(label) | InstructionOp::CondJump(.., label) => Some(label),
+            _ => None,
+        }
+    }
+}
+
 impl Display for InstructionOp {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            InstructionOp::AssignFromConst(target, value) =>
-                write!(f, "R{} := {}", target, value),
-            InstructionOp::AssignFromRegister(target, source) =>
-                write!(f, "R{} := R{}", target, source),
-            InstructionOp::Load(target, source) =>
-                write!(f, "R{} := [R{}]", target, source),
-            InstructionOp::Store(target, source) =>
-                write!(f, "[R{}] := R{}", target, source),
-            InstructionOp::ArithmeticRegOpReg(target, source1, op, source2) =>
-                write!(f, "R{} := R{} {} R{}", target, source1, op, source2),
-            InstructionOp::ArithmeticRegOpConst(target, source, op, value) =>
-                write!(f, "R{} := R{} {} {}", target, source, op, value),
+            InstructionOp::Assign(target, value) =>
+                write!(f, "{} := {}", target, value),
+            InstructionOp::Arithmetic(target, a, op, b) =>
+                write!(f, "{} := {} {} {}", target, a, op, b),
             InstructionOp::Jump(label) =>
                 write!(f, "goto {}", label),
-            InstructionOp::CondJumpRegRelReg(reg1, rel, reg2, label) =>
-                write!(f, "if (R{} {} R{}) goto {}", reg1, rel, reg2, label),
-            InstructionOp::CondJumpRegRelConst(reg, rel, value, label) =>
-                write!(f, "if (R{} {} {}) goto {}", reg, rel, value, label),
-            InstructionOp::Read(reg) =>
-                write!(f, "R{} := read()", reg),
-            InstructionOp::Write(reg) =>
-                write!(f, "write(R{})", reg),
+            InstructionOp::CondJump(a, rel, b, label) =>
+                write!(f, "if ({} {} {}) goto {}", a, rel, b, label),
+            InstructionOp::Read(target) =>
+                write!(f, "{} := read()", target),
+            InstructionOp::Write(value) =>
+                write!(f, "write({})", value),
             InstructionOp::Halt =>
                 write!(f, "halt"),
             InstructionOp::Empty =>
                 write!(f, ""),
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,19 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IoMode {
+    // reading past the end of the input tape is an error
+    #[default]
+    Tape,
+    // reading past the end of the input tape waits for a value to be pushed
+    Interactive,
+}
+
+impl Display for IoMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IoMode::Tape => write!(f, "tape"),
+            IoMode::Interactive => write!(f, "interactive"),
+        }
+    }
+}
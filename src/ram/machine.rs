@@ -1,35 +1,60 @@
 use std::collections::HashMap;
+use std::sync::Arc;
+use crate::ram::cycle::LoopDetector;
+use crate::ram::execution::Execution;
+use crate::ram::execution_mode::ExecutionMode;
 use crate::ram::instruction::Instruction;
 use crate::ram::instruction_op::InstructionOp;
-use crate::ram::op::{Op};
-use crate::ram::rel::{Rel};
+use crate::ram::expression;
+use crate::ram::operand::Operand;
+use crate::ram::io_mode::IoMode;
+use crate::ram::profile::Profile;
+use crate::ram::program::Program;
+use crate::ram::rasp;
+use crate::ram::strict_mode::StrictMode;
+use crate::ram::run::{RunOptions, RunOutcome, RunReport, RunStats};
 use crate::ram::types::{Index, Label, Number};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct RamMachine {
-    memory: HashMap<Number, Number>,
-    program: Vec<Instruction>,
-    instruction_pointer: Index,
-    labels: HashMap<Label, Index>,
-    input_pointer: Index,
-    input_tape: Vec<Number>,
-    output_tape: Vec<Number>,
-    instruction_count: Index,
+    program: Arc<Program>,
+    state: Execution,
+    label_names: HashMap<Index, Label>,
+    io_mode: IoMode,
+    profile: Option<Profile>,
+    loop_detector: Option<LoopDetector>,
+    strict_mode: StrictMode,
+    memory_limit: Option<Number>,
+    warnings: Vec<String>,
 }
 
 impl RamMachine {
     pub fn new(program: Vec<Instruction>) -> Self {
-        let labels = RamMachine::extract_labels(&program);
+        RamMachine::from_program(Arc::new(Program::new(program)))
+    }
+
+    // a fresh machine for an already parsed program, the program itself is not copied
+    pub fn from_program(program: Arc<Program>) -> Self {
+        RamMachine::resume(program, Execution::default())
+    }
 
+    // continues a run from a saved state, a RASP run keeps the program it left in memory,
+    // the io mode, profiling and checks are not part of the state and are set again with the `with_*` builders
+    pub fn resume(program: Arc<Program>, state: Execution) -> Self {
+        let label_names = match state.mode {
+            ExecutionMode::Ram => HashMap::new(),
+            ExecutionMode::Rasp(_) => Self::label_names(&program),
+        };
         let mut machine = RamMachine {
-            memory: HashMap::new(),
             program,
-            instruction_pointer: 0,
-            labels,
-            input_pointer: 0,
-            input_tape: Vec::new(),
-            output_tape: Vec::new(),
-            instruction_count: 0,
+            state,
+            label_names,
+            io_mode: IoMode::Tape,
+            profile: None,
+            loop_detector: None,
+            strict_mode: StrictMode::Off,
+            memory_limit: None,
+            warnings: Vec::new(),
         };
         
         machine.skip_empty();
@@ -37,163 +62,349 @@ impl RamMachine {
     }
     
     pub fn with_input(mut self, input_tape: Vec<Number>) -> Self {
-        self.input_tape = input_tape;
+        self.state.input_tape = input_tape;
         self
     }
 
-    fn extract_labels(program: &[Instruction]) -> HashMap<Label, Index> {
-        let mut labels = HashMap::new();
-        for (i, instruction) in program.iter().enumerate() {
-            if let Some(label) = &instruction.label {
-                labels.insert(label.clone(), i);
+    pub fn with_mode(mut self, mode: ExecutionMode) -> Result<Self, String> {
+        if let ExecutionMode::Rasp(base) = mode {
+            if base < 0 {
+                return Err(format!("RASP base {} is negative", base));
             }
+            // load the encoded program into memory, it is fetched from there from now on
+            let cells = rasp::encode(self.program.instructions(), self.program.labels())?;
+            for (offset, cell) in cells.into_iter().enumerate() {
+                self.state.memory.insert(base + offset as Number, cell);
+            }
+            self.label_names = Self::label_names(&self.program);
         }
-        labels
+        self.state.mode = mode;
+        self.skip_empty();
+        Ok(self)
+    }
+
+    // jumps are decoded back to the first label of their target
+    fn label_names(program: &Program) -> HashMap<Index, Label> {
+        program.instructions().iter().enumerate()
+            .filter_map(|(index, instruction)| Some((index, instruction.labels.first()?.clone())))
+            .collect()
+    }
+
+    pub fn with_io_mode(mut self, io_mode: IoMode) -> Self {
+        self.io_mode = io_mode;
+        self
+    }
+
+    // counts executions, branches and memory accesses from now on
+    pub fn with_profiling(mut self) -> Self {
+        self.profile = Some(Profile::new(self.program.len()));
+        self
+    }
+
+    // fails as soon as the machine returns to a state it was already in,
+    // every state is kept so this is meant for small programs
+    pub fn with_loop_detection(mut self) -> Self {
+        self.loop_detector = Some(LoopDetector::new());
+        self
+    }
+
+    pub fn with_strict_mode(mut self, strict_mode: StrictMode) -> Self {
+        self.strict_mode = strict_mode;
+        self
+    }
+
+    // addresses from this one up are invalid in strict mode
+    pub fn with_memory_limit(mut self, limit: Number) -> Self {
+        self.memory_limit = Some(limit);
+        self
     }
 
     pub fn get(&self, reg: Number) -> Number {
-        match self.memory.get(&reg) {
-            None => { 0 }
-            Some(value) => { *value }
+        self.state.get(reg)
+    }
+
+    // reads made by the executed instructions, unlike `get` they are profiled and checked
+    fn read(&mut self, reg: Number) -> Result<Number, String> {
+        if let Some(profile) = &mut self.profile {
+            profile.record_read(reg);
+        }
+        if self.strict_mode != StrictMode::Off {
+            self.check_address(reg)?;
+            if !self.state.memory.contains_key(&reg) {
+                self.report(format!("Read of uninitialized R{}", reg))?;
+            }
+        }
+        Ok(self.get(reg))
+    }
+
+    fn set(&mut self, reg: Number, value: Number) -> Result<(), String> {
+        if let Some(profile) = &mut self.profile {
+            profile.record_write(reg);
+        }
+        if self.strict_mode != StrictMode::Off {
+            self.check_address(reg)?;
+        }
+        if self.state.memory.insert(reg, value) != Some(value) {
+            self.state.changed.push(reg);
+        }
+        Ok(())
+    }
+
+    fn value(&mut self, operand: Operand) -> Result<Number, String> {
+        match operand {
+            Operand::Constant(value) => Ok(value),
+            // temporaries are neither profiled nor checked
+            Operand::Register(reg) if expression::is_temporary(reg) => Ok(self.state.temporaries[expression::slot(reg)]),
+            Operand::Register(reg) => self.read(reg),
+            Operand::Indirect(reg) => {
+                let address = self.read(reg)?;
+                self.read(address)
+            }
+        }
+    }
+
+    fn assign(&mut self, target: Operand, value: Number) -> Result<(), String> {
+        match target {
+            Operand::Constant(constant) => Err(format!("Cannot assign to the constant {}", constant)),
+            Operand::Register(reg) if expression::is_temporary(reg) => {
+                self.state.temporaries[expression::slot(reg)] = value;
+                Ok(())
+            }
+            Operand::Register(reg) => self.set(reg, value),
+            Operand::Indirect(reg) => {
+                let address = self.read(reg)?;
+                self.set(address, value)
+            }
         }
     }
 
-    fn set(&mut self, reg: Number, value: Number) {
-        self.memory.insert(reg, value);
+    // the first instruction of a source line, temporaries are never alive across lines
+    fn starts_line(&self, index: Index) -> bool {
+        index == 0 || self.program.line(index - 1) != self.program.line(index)
+    }
+
+    fn check_address(&mut self, address: Number) -> Result<(), String> {
+        if address < 0 {
+            return self.report(format!("Negative address {}", address));
+        }
+        match self.memory_limit {
+            Some(limit) if address >= limit => {
+                self.report(format!("Address {} is beyond the memory limit of {}", address, limit))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // fails in strict mode, otherwise the problem is logged once per line
+    fn report(&mut self, problem: String) -> Result<(), String> {
+        let problem = format!("Line {}: {}", self.program.line(self.state.instruction_pointer) + 1, problem);
+        match self.strict_mode {
+            StrictMode::Off => Ok(()),
+            StrictMode::Warn => {
+                if !self.warnings.contains(&problem) {
+                    self.warnings.push(problem);
+                }
+                Ok(())
+            }
+            StrictMode::Error => Err(problem),
+        }
     }
 
     pub fn get_memory(&self) -> &HashMap<Number, Number> {
-        &self.memory
+        self.state.get_memory()
     }
     
     pub fn get_input_pointer(&self) -> Index {
-        self.input_pointer
+        self.state.get_input_pointer()
     }
 
     pub fn get_input(&self) -> &Vec<Number> {
-        &self.input_tape
+        self.state.get_input()
     }
 
     pub fn get_output(&self) -> &Vec<Number> {
-        &self.output_tape
+        self.state.get_output()
     }
     
     pub fn get_program(&self) -> &Vec<Instruction> {
+        self.program.instructions()
+    }
+
+    pub fn get_shared_program(&self) -> &Arc<Program> {
         &self.program
     }
 
+    pub fn get_state(&self) -> &Execution {
+        &self.state
+    }
+
+    pub fn into_state(self) -> Execution {
+        self.state
+    }
+
+    // addresses whose value changed during the last step
+    pub fn get_changed(&self) -> &[Number] {
+        &self.state.changed
+    }
+
     pub fn get_instruction_pointer(&self) -> Index {
-        self.instruction_pointer
+        self.state.get_instruction_pointer()
     }
 
     pub fn get_instruction_count(&self) -> Index {
-        self.instruction_count
+        self.state.get_instruction_count()
+    }
+
+    pub fn get_profile(&self) -> Option<&Profile> {
+        self.profile.as_ref()
+    }
+
+    pub fn get_strict_mode(&self) -> StrictMode {
+        self.strict_mode
+    }
+
+    pub fn get_memory_limit(&self) -> Option<Number> {
+        self.memory_limit
+    }
+
+    // problems found in the strict warning mode, each one only once
+    pub fn get_warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    pub fn get_mode(&self) -> ExecutionMode {
+        self.state.get_mode()
     }
 
-    fn apply_op(&self, op: Op, a: Number, b: Number) -> Number {
-        match op {
-            Op::Add => a + b,
-            Op::Sub => a - b,
-            Op::Mul => a * b,
-            Op::Div => a / b,
+    pub fn get_io_mode(&self) -> IoMode {
+        self.io_mode
+    }
+
+    pub fn push_input(&mut self, value: Number) {
+        self.state.input_tape.push(value);
+    }
+
+    // the next step reads past the end of the tape and a value has to be pushed first
+    pub fn is_awaiting_input(&self) -> bool {
+        self.io_mode == IoMode::Interactive
+            && self.state.instruction_pointer < self.program.len()
+            && self.state.input_pointer >= self.state.input_tape.len()
+            && matches!(self.fetch(), Ok(InstructionOp::Read(_)))
+    }
+
+    // the instruction at the instruction pointer, decoded from memory in RASP mode
+    pub fn fetch(&self) -> Result<InstructionOp, String> {
+        self.instruction_at(self.state.instruction_pointer)
+    }
+
+    // what would run at the given index, in RASP mode this follows the changes a program made to its code
+    pub fn instruction_at(&self, index: Index) -> Result<InstructionOp, String> {
+        match self.state.mode {
+            ExecutionMode::Ram => Ok(self.program.instructions()[index].op.clone()),
+            ExecutionMode::Rasp(base) => {
+                let address = rasp::address_of(base, index);
+                let cells = [0, 1, 2, 3].map(|offset| self.get(address + offset));
+                rasp::decode(cells, &self.label_names)
+                    .map_err(|err| format!("{} at address {}", err, address))
+            }
+        }
+    }
+
+    fn jump(&mut self, label: &Label) -> Result<bool, String> {
+        let index = match self.program.label(label) {
+            Some(index) => Some(index),
+            // self-modified jumps may point at unlabeled instructions
+            None if self.state.mode != ExecutionMode::Ram => label.parse::<Index>().ok(),
+            None => None,
+        };
+        match index {
+            Some(index) => {
+                self.state.instruction_pointer = index;
+                self.skip_empty();
+                Ok(self.state.instruction_pointer >= self.program.len())
+            }
+            None => Err(format!("Label {} not found", label)),
         }
     }
 
-    fn apply_rel(&self, rel: Rel, a: Number, b: Number) -> bool {
-        match rel {
-            Rel::Lt => a < b,
-            Rel::Gt => a > b,
-            Rel::Le => a <= b,
-            Rel::Ge => a >= b,
-            Rel::Eq => a == b,
-            Rel::Ne => a != b,
+    fn branch(&mut self, taken: bool) -> bool {
+        if let Some(profile) = &mut self.profile {
+            profile.record_branch(self.state.instruction_pointer, taken);
         }
+        taken
     }
 
     pub fn step(&mut self) -> Result<bool, String> {
-        if self.instruction_pointer >= self.program.len() {
+        if self.state.instruction_pointer >= self.program.len() {
             return Ok(true);  // end of program
         }
 
-        self.instruction_count += 1;
-        let instruction = &self.program[self.instruction_pointer];
-        match &instruction.op {
-            // Ri ∶= c
-            InstructionOp::AssignFromConst(target, value) => {
-                self.set(*target, *value);
-            }
-            // Ri ∶= Rj
-            InstructionOp::AssignFromRegister(target, source) => {
-                self.set(*target, self.get(*source));
-            }
-            // Ri ∶= [Rj]
-            InstructionOp::Load(target, source) => {
-                self.set(*target, self.get(self.get(*source)));
-            }
-            // [Ri] ∶= Rj
-            InstructionOp::Store(target, source) => {
-                self.set(self.get(*target), self.get(*source));
+        let op = self.fetch()?;
+        if self.is_awaiting_input() {
+            // not counted, the same instruction is retried once input arrives
+            return Err("Waiting for input".to_string())
+        }
+
+        // within a lowered line the temporaries are part of the state, so only whole lines are compared
+        let starts_line = self.starts_line(self.state.instruction_pointer);
+        if let Some(detector) = self.loop_detector.as_mut().filter(|_| starts_line) {
+            if let Some(cycle) = detector.check(self.state.instruction_count, self.state.instruction_pointer, self.state.input_pointer, &self.state.memory) {
+                return Err(format!(
+                    "Infinite loop detected: the state at step {} repeats every {} steps from line {}",
+                    cycle.first_step, cycle.length, self.program.line(cycle.instruction) + 1,
+                ));
             }
-            // Ri ∶= Rj op Rk
-            InstructionOp::ArithmeticRegOpReg(target, source1, op, source2) => {
-                let result = self.apply_op(*op, self.get(*source1), self.get(*source2));
-                self.set(*target, result);
+        }
+
+        self.state.instruction_count += 1;
+        self.state.changed.clear();
+        if let Some(profile) = &mut self.profile {
+            profile.record_execution(self.state.instruction_pointer);
+        }
+
+        match &op {
+            // x ∶= y
+            InstructionOp::Assign(target, value) => {
+                let value = self.value(*value)?;
+                self.assign(*target, value)?;
             }
-            // Ri ∶= Rj op c
-            InstructionOp::ArithmeticRegOpConst(target, source, op, value) => {
-                let result = self.apply_op(*op, self.get(*source), *value);
-                self.set(*target, result);
+            // x ∶= y op z
+            InstructionOp::Arithmetic(target, a, op, b) => {
+                let (a, b) = (self.value(*a)?, self.value(*b)?);
+                self.assign(*target, op.apply(a, b)?)?;
             }
             // goto ℓ
-            InstructionOp::Jump(label) => {
-                if let Some(&index) = self.labels.get(label) {
-                    self.instruction_pointer = index;
-                    return Ok(false);
-                }
-                return Err(format!("Label {} not found", label))
-            }
-            // if (Ri rel Rj) goto ℓ
-            InstructionOp::CondJumpRegRelReg(reg1, rel, reg2, label) => {
-                if self.apply_rel(*rel, self.get(*reg1), self.get(*reg2)) {
-                    if let Some(&index) = self.labels.get(label) {
-                        self.instruction_pointer = index;
-                        return Ok(false);
-                    }
-                    return Err(format!("Label {} not found", label))
-                }
-            }
-            // if (Ri rel c) goto ℓ
-            InstructionOp::CondJumpRegRelConst(reg, rel, value, label) => {
-                if self.apply_rel(*rel, self.get(*reg), *value) {
-                    if let Some(&index) = self.labels.get(label) {
-                        self.instruction_pointer = index;
-                        return Ok(false);
-                    }
-                    return Err(format!("Label {} not found", label))
+            InstructionOp::Jump(label) => return self.jump(label),
+            // if (x rel y) goto ℓ
+            InstructionOp::CondJump(a, rel, b, label) => {
+                let (a, b) = (self.value(*a)?, self.value(*b)?);
+                if self.branch(rel.holds(a, b)) {
+                    return self.jump(label);
                 }
             }
             // halt
             InstructionOp::Halt => return Ok(true),
-            // Ri := read()
-            InstructionOp::Read(reg) => {
-                if self.input_pointer >= self.input_tape.len() {
+            // x := read()
+            InstructionOp::Read(target) => {
+                if self.state.input_pointer >= self.state.input_tape.len() {
                     return Err("Attempt to read from empty tape".to_string())
                 }
-                let val = self.input_tape[self.input_pointer];
-                self.input_pointer += 1;
-                self.set(*reg, val);
+                let val = self.state.input_tape[self.state.input_pointer];
+                self.assign(*target, val)?;
+                self.state.input_pointer += 1;
             }
-            // write(Ri)
-            InstructionOp::Write(reg) => {
-                self.output_tape.push(self.get(*reg));
+            // write(x)
+            InstructionOp::Write(value) => {
+                let value = self.value(*value)?;
+                self.state.output_tape.push(value);
             }
             InstructionOp::Empty => { return Err("Empty instruction should be skipped".to_string()) }
         }
 
-        self.instruction_pointer += 1;
+        self.state.instruction_pointer += 1;
         self.skip_empty();
         
-        if self.instruction_pointer >= self.program.len() {
+        if self.state.instruction_pointer >= self.program.len() {
             return Ok(true);  // end of program
         }
         
@@ -201,14 +412,57 @@ impl RamMachine {
     }
     
     fn skip_empty(&mut self) {
-        while self.instruction_pointer != self.program.len() && self.program[self.instruction_pointer].op == InstructionOp::Empty {
-            self.instruction_pointer += 1;
+        while self.state.instruction_pointer < self.program.len() && self.fetch() == Ok(InstructionOp::Empty) {
+            self.state.instruction_pointer += 1;
         }
     }
 
+    // runs to the end of the program, the first error is returned
     pub fn run(&mut self) -> Result<(), String> {
-        println!("\nRunning RAM machine!");
-        while !self.step()? {}
-        Ok(())
+        match self.run_with(RunOptions::new()).outcome {
+            RunOutcome::Error(message) => Err(message),
+            RunOutcome::AwaitingInput => Err("Waiting for input".to_string()),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn run_with(&mut self, mut options: RunOptions) -> RunReport {
+        // the io mode of the options only applies to this run
+        let io_mode = self.io_mode;
+        if let Some(run_io_mode) = options.io_mode() {
+            self.io_mode = run_io_mode;
+        }
+
+        let mut steps = 0;
+        let outcome = loop {
+            if self.state.instruction_pointer >= self.program.len() {
+                break RunOutcome::FellOffEnd;
+            }
+            if options.step_limit().is_some_and(|limit| steps >= limit) {
+                break RunOutcome::LimitExceeded;
+            }
+            // the instruction the run starts on is executed, so a stopped run can be resumed
+            if steps > 0 && options.is_breakpoint(self.state.instruction_pointer) {
+                break RunOutcome::Breakpoint(self.state.instruction_pointer);
+            }
+            if self.is_awaiting_input() {
+                break RunOutcome::AwaitingInput;
+            }
+
+            let result = self.step();
+            if result.is_ok() {
+                steps += 1;
+                options.observe(self);
+            }
+            match result {
+                Ok(false) => {}
+                Ok(true) if self.state.instruction_pointer >= self.program.len() => break RunOutcome::FellOffEnd,
+                Ok(true) => break RunOutcome::Halted,
+                Err(message) => break RunOutcome::Error(message),
+            }
+        };
+
+        self.io_mode = io_mode;
+        RunReport { outcome, stats: RunStats::of(self, steps) }
     }
 }
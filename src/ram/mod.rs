@@ -3,4 +3,19 @@ pub mod op;
 pub mod rel;
 pub mod instruction_op;
 pub mod types;
-pub mod instruction;
\ No newline at end of file
+pub mod instruction;
+pub mod execution_mode;
+pub mod rasp;
+pub mod io_mode;
+pub mod watch;
+pub mod profile;
+pub mod cycle;
+pub mod run;
+pub mod program;
+pub mod execution;
+pub mod batch;
+pub mod compiled;
+pub mod strict_mode;
+pub mod operand;
+pub mod dialect;
+pub mod expression;
\ No newline at end of file
@@ -1,4 +1,5 @@
 use std::fmt::Display;
+use crate::ram::types::Number;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Op {
@@ -8,6 +9,22 @@ pub enum Op {
     Div,
 }
 
+impl Op {
+    // overflow and division by zero are errors of the program, not of the simulator
+    pub fn apply(self, a: Number, b: Number) -> Result<Number, String> {
+        if self == Op::Div && b == 0 {
+            return Err("Division by zero".to_string());
+        }
+        let result = match self {
+            Op::Add => a.checked_add(b),
+            Op::Sub => a.checked_sub(b),
+            Op::Mul => a.checked_mul(b),
+            Op::Div => a.checked_div(b),
+        };
+        result.ok_or_else(|| format!("Overflow in {} {} {}", a, self, b))
+    }
+}
+
 impl Display for Op {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -0,0 +1,22 @@
+use std::fmt::Display;
+use crate::ram::types::Number;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operand {
+    // c
+    Constant(Number),
+    // Ri
+    Register(Number),
+    // [Ri], the cell at the address stored in Ri
+    Indirect(Number),
+}
+
+impl Display for Operand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Operand::Constant(value) => write!(f, "{}", value),
+            Operand::Register(reg) => write!(f, "R{}", reg),
+            Operand::Indirect(reg) => write!(f, "[R{}]", reg),
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use crate::ram::instruction_op::InstructionOp;
+use crate::ram::program::Program;
+use crate::ram::types::{Index, Number};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BranchCount {
+    pub taken: u64,
+    pub not_taken: u64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AccessCount {
+    pub reads: u64,
+    pub writes: u64,
+}
+
+// how often every instruction ran, where conditional jumps went and which cells were used
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Profile {
+    executions: Vec<u64>,
+    branches: HashMap<Index, BranchCount>,
+    memory: HashMap<Number, AccessCount>,
+}
+
+impl Profile {
+    pub fn new(program_length: usize) -> Profile {
+        Profile {
+            executions: vec![0; program_length],
+            ..Profile::default()
+        }
+    }
+
+    pub fn record_execution(&mut self, index: Index) {
+        if let Some(count) = self.executions.get_mut(index) {
+            *count += 1;
+        }
+    }
+
+    pub fn record_branch(&mut self, index: Index, taken: bool) {
+        let count = self.branches.entry(index).or_default();
+        if taken {
+            count.taken += 1;
+        } else {
+            count.not_taken += 1;
+        }
+    }
+
+    pub fn record_read(&mut self, address: Number) {
+        self.memory.entry(address).or_default().reads += 1;
+    }
+
+    pub fn record_write(&mut self, address: Number) {
+        self.memory.entry(address).or_default().writes += 1;
+    }
+
+    pub fn executions(&self, index: Index) -> u64 {
+        self.executions.get(index).copied().unwrap_or(0)
+    }
+
+    pub fn max_executions(&self) -> u64 {
+        self.executions.iter().copied().max().unwrap_or(0)
+    }
+
+    pub fn branch(&self, index: Index) -> Option<BranchCount> {
+        self.branches.get(&index).copied()
+    }
+
+    pub fn access(&self, address: Number) -> AccessCount {
+        self.memory.get(&address).copied().unwrap_or_default()
+    }
+
+    fn sorted_branches(&self) -> Vec<(Index, BranchCount)> {
+        let mut branches: Vec<_> = self.branches.iter().map(|(&index, &count)| (index, count)).collect();
+        branches.sort_by_key(|(index, _)| *index);
+        branches
+    }
+
+    fn sorted_memory(&self) -> Vec<(Number, AccessCount)> {
+        let mut memory: Vec<_> = self.memory.iter().map(|(&address, &count)| (address, count)).collect();
+        memory.sort_by_key(|(address, _)| *address);
+        memory
+    }
+
+    pub fn to_text(&self, program: &Program) -> String {
+        let mut report = String::new();
+        let total: u64 = self.executions.iter().sum();
+        let _ = writeln!(report, "Instructions executed: {}", total);
+
+        let _ = writeln!(report, "\n{:>6} {:>10}  instruction", "line", "count");
+        for (index, instruction) in program.instructions().iter().enumerate() {
+            if instruction.op != InstructionOp::Empty {
+                let _ = writeln!(report, "{:>6} {:>10}  {}", program.line(index) + 1, self.executions(index), instruction.op);
+            }
+        }
+
+        let branches = self.sorted_branches();
+        if !branches.is_empty() {
+            let _ = writeln!(report, "\n{:>6} {:>10} {:>10}", "branch", "taken", "not taken");
+            for (index, count) in branches {
+                let _ = writeln!(report, "{:>6} {:>10} {:>10}", program.line(index) + 1, count.taken, count.not_taken);
+            }
+        }
+
+        let memory = self.sorted_memory();
+        if !memory.is_empty() {
+            let _ = writeln!(report, "\n{:>6} {:>10} {:>10}", "cell", "reads", "writes");
+            for (address, count) in memory {
+                let _ = writeln!(report, "{:>6} {:>10} {:>10}", format!("R{}", address), count.reads, count.writes);
+            }
+        }
+        report
+    }
+
+    // `index` is the instruction, `line` the line of the file it is on, counted from one
+    pub fn to_json(&self, program: &Program) -> String {
+        let instructions: Vec<String> = program.instructions().iter().enumerate()
+            .filter(|(_, instruction)| instruction.op != InstructionOp::Empty)
+            .map(|(index, instruction)| format!(
+                "{{\"index\": {}, \"line\": {}, \"count\": {}, \"instruction\": \"{}\"}}",
+                index, program.line(index) + 1, self.executions(index), escape_json(&instruction.op.to_string()),
+            ))
+            .collect();
+        let branches: Vec<String> = self.sorted_branches().iter()
+            .map(|(index, count)| format!(
+                "{{\"index\": {}, \"line\": {}, \"taken\": {}, \"not_taken\": {}}}",
+                index, program.line(*index) + 1, count.taken, count.not_taken,
+            ))
+            .collect();
+        let memory: Vec<String> = self.sorted_memory().iter()
+            .map(|(address, count)| format!(
+                "{{\"address\": {}, \"reads\": {}, \"writes\": {}}}",
+                address, count.reads, count.writes,
+            ))
+            .collect();
+
+        format!(
+            "{{\n  \"instructions_executed\": {},\n  \"instructions\": [{}],\n  \"branches\": [{}],\n  \"memory\": [{}]\n}}",
+            self.executions.iter().sum::<u64>(),
+            json_list(&instructions),
+            json_list(&branches),
+            json_list(&memory),
+        )
+    }
+}
+
+fn json_list(items: &[String]) -> String {
+    if items.is_empty() {
+        return String::new();
+    }
+    format!("\n    {}\n  ", items.join(",\n    "))
+}
+
+fn escape_json(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if c.is_control() => {
+                let _ = write!(escaped, "\\u{:04x}", c as u32);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
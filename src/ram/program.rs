@@ -0,0 +1,70 @@
+use std::collections::HashMap;
+use crate::ram::instruction::Instruction;
+use crate::ram::instruction_op::InstructionOp;
+use crate::ram::types::{Index, Label};
+
+// parsed instructions with their labels resolved, never changes while running
+// and can be shared between any number of machines behind an `Arc`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Program {
+    instructions: Vec<Instruction>,
+    labels: HashMap<Label, Index>,
+    // source line of every instruction, lowered expressions take up several
+    lines: Vec<Index>,
+}
+
+impl Program {
+    pub fn new(instructions: Vec<Instruction>) -> Program {
+        let mut labels = HashMap::new();
+        for (i, instruction) in instructions.iter().enumerate() {
+            for label in &instruction.labels {
+                labels.insert(label.clone(), i);
+            }
+        }
+        let lines = (0..instructions.len()).collect();
+        Program { instructions, labels, lines }
+    }
+
+    pub fn with_lines(mut self, lines: Vec<Index>) -> Program {
+        assert_eq!(lines.len(), self.instructions.len(), "every instruction needs a line");
+        self.lines = lines;
+        self
+    }
+
+    pub fn instructions(&self) -> &Vec<Instruction> {
+        &self.instructions
+    }
+
+    pub fn labels(&self) -> &HashMap<Label, Index> {
+        &self.labels
+    }
+
+    pub fn label(&self, label: &str) -> Option<Index> {
+        self.labels.get(label).copied()
+    }
+
+    // zero-based source line the instruction comes from
+    pub fn line(&self, index: Index) -> Index {
+        self.lines.get(index).copied().unwrap_or(index)
+    }
+
+    // instructions that can run right after this one, `len()` stands for the end of the program
+    // and jumps to missing labels go nowhere
+    pub fn successors(&self, index: Index) -> Vec<Index> {
+        let op = &self.instructions[index].op;
+        let target = op.target_label().and_then(|label| self.label(label));
+        match op {
+            InstructionOp::Halt => Vec::new(),
+            InstructionOp::Jump(_) => target.into_iter().collect(),
+            _ => std::iter::once(index + 1).chain(target).collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.instructions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instructions.is_empty()
+    }
+}
@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+use crate::ram::instruction::Instruction;
+use crate::ram::instruction_op::InstructionOp;
+use crate::ram::op::Op;
+use crate::ram::operand::Operand::{Constant, Indirect, Register};
+use crate::ram::rel::Rel;
+use crate::ram::types::{Index, Label, Number};
+
+// every instruction occupies a fixed number of cells: opcode followed by operands
+pub const INSTRUCTION_WIDTH: Number = 4;
+// the code takes up R1000 and above unless another base is given, writes there are how a program modifies itself
+pub const DEFAULT_BASE: Number = 1000;
+
+const HALT: Number = 0;
+const ASSIGN_FROM_CONST: Number = 1;
+const ASSIGN_FROM_REGISTER: Number = 2;
+const LOAD: Number = 3;
+const STORE: Number = 4;
+// arithmetic and conditional opcodes are offset by the operator
+const ARITHMETIC_REG_OP_REG: Number = 10;
+const ARITHMETIC_REG_OP_CONST: Number = 20;
+const JUMP: Number = 30;
+const COND_JUMP_REG_REL_REG: Number = 40;
+const COND_JUMP_REG_REL_CONST: Number = 50;
+const READ: Number = 60;
+const WRITE: Number = 61;
+const EMPTY: Number = 99;
+
+pub fn address_of(base: Number, index: Index) -> Number {
+    base + index as Number * INSTRUCTION_WIDTH
+}
+
+pub fn encode(program: &[Instruction], labels: &HashMap<Label, Index>) -> Result<Vec<Number>, String> {
+    let mut cells = Vec::with_capacity(program.len() * INSTRUCTION_WIDTH as usize);
+    for instruction in program {
+        cells.extend(encode_op(&instruction.op, labels)?);
+    }
+    Ok(cells)
+}
+
+pub fn encode_op(op: &InstructionOp, labels: &HashMap<Label, Index>) -> Result<[Number; 4], String> {
+    let target = |label: &Label| -> Result<Number, String> {
+        labels.get(label)
+            .map(|&index| index as Number)
+            .ok_or_else(|| format!("Label {} not found", label))
+    };
+
+    let cells = match op {
+        InstructionOp::Assign(Register(reg), Constant(value)) => [ASSIGN_FROM_CONST, *reg, *value, 0],
+        InstructionOp::Assign(Register(reg), Register(source)) => [ASSIGN_FROM_REGISTER, *reg, *source, 0],
+        InstructionOp::Assign(Register(reg), Indirect(source)) => [LOAD, *reg, *source, 0],
+        InstructionOp::Assign(Indirect(reg), Register(source)) => [STORE, *reg, *source, 0],
+        InstructionOp::Arithmetic(Register(reg), Register(source1), op, Register(source2)) =>
+            [ARITHMETIC_REG_OP_REG + op_code(*op), *reg, *source1, *source2],
+        InstructionOp::Arithmetic(Register(reg), Register(source), op, Constant(value)) =>
+            [ARITHMETIC_REG_OP_CONST + op_code(*op), *reg, *source, *value],
+        InstructionOp::Jump(label) => [JUMP, target(label)?, 0, 0],
+        InstructionOp::CondJump(Register(reg1), rel, Register(reg2), label) =>
+            [COND_JUMP_REG_REL_REG + rel_code(*rel), *reg1, *reg2, target(label)?],
+        InstructionOp::CondJump(Register(reg), rel, Constant(value), label) =>
+            [COND_JUMP_REG_REL_CONST + rel_code(*rel), *reg, *value, target(label)?],
+        InstructionOp::Read(Register(reg)) => [READ, *reg, 0, 0],
+        InstructionOp::Write(Register(reg)) => [WRITE, *reg, 0, 0],
+        InstructionOp::Halt => [HALT, 0, 0, 0],
+        InstructionOp::Empty => [EMPTY, 0, 0, 0],
+        // the opcodes only cover the textbook forms
+        op => return Err(format!("'{}' has no RASP encoding", op)),
+    };
+    Ok(cells)
+}
+
+// jump targets are instruction indices, named after their label when there is one
+pub fn decode(cells: [Number; 4], names: &HashMap<Index, Label>) -> Result<InstructionOp, String> {
+    let [opcode, a, b, c] = cells;
+    let name = |index: Number| -> Result<Label, String> {
+        if index < 0 {
+            return Err(format!("Invalid jump target {}", index));
+        }
+        Ok(names.get(&(index as Index)).cloned().unwrap_or_else(|| index.to_string()))
+    };
+
+    let op = match opcode {
+        HALT => InstructionOp::Halt,
+        ASSIGN_FROM_CONST => InstructionOp::Assign(Register(a), Constant(b)),
+        ASSIGN_FROM_REGISTER => InstructionOp::Assign(Register(a), Register(b)),
+        LOAD => InstructionOp::Assign(Register(a), Indirect(b)),
+        STORE => InstructionOp::Assign(Indirect(a), Register(b)),
+        JUMP => InstructionOp::Jump(name(a)?),
+        READ => InstructionOp::Read(Register(a)),
+        WRITE => InstructionOp::Write(Register(a)),
+        EMPTY => InstructionOp::Empty,
+        _ => {
+            let (group, offset) = (opcode - opcode % 10, opcode % 10);
+            match (group, op_from_code(offset), rel_from_code(offset)) {
+                (ARITHMETIC_REG_OP_REG, Some(op), _) => InstructionOp::Arithmetic(Register(a), Register(b), op, Register(c)),
+                (ARITHMETIC_REG_OP_CONST, Some(op), _) => InstructionOp::Arithmetic(Register(a), Register(b), op, Constant(c)),
+                (COND_JUMP_REG_REL_REG, _, Some(rel)) => InstructionOp::CondJump(Register(a), rel, Register(b), name(c)?),
+                (COND_JUMP_REG_REL_CONST, _, Some(rel)) => InstructionOp::CondJump(Register(a), rel, Constant(b), name(c)?),
+                _ => return Err(format!("Invalid opcode {}", opcode)),
+            }
+        }
+    };
+    Ok(op)
+}
+
+fn op_code(op: Op) -> Number {
+    match op {
+        Op::Add => 0,
+        Op::Sub => 1,
+        Op::Mul => 2,
+        Op::Div => 3,
+    }
+}
+
+fn op_from_code(code: Number) -> Option<Op> {
+    match code {
+        0 => Some(Op::Add),
+        1 => Some(Op::Sub),
+        2 => Some(Op::Mul),
+        3 => Some(Op::Div),
+        _ => None,
+    }
+}
+
+fn rel_code(rel: Rel) -> Number {
+    match rel {
+        Rel::Lt => 0,
+        Rel::Gt => 1,
+        Rel::Le => 2,
+        Rel::Ge => 3,
+        Rel::Eq => 4,
+        Rel::Ne => 5,
+    }
+}
+
+fn rel_from_code(code: Number) -> Option<Rel> {
+    match code {
+        0 => Some(Rel::Lt),
+        1 => Some(Rel::Gt),
+        2 => Some(Rel::Le),
+        3 => Some(Rel::Ge),
+        4 => Some(Rel::Eq),
+        5 => Some(Rel::Ne),
+        _ => None,
+    }
+}
@@ -1,4 +1,5 @@
 use std::fmt::Display;
+use crate::ram::types::Number;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Rel {
@@ -10,6 +11,19 @@ pub enum Rel {
     Ne,
 }
 
+impl Rel {
+    pub fn holds(self, a: Number, b: Number) -> bool {
+        match self {
+            Rel::Lt => a < b,
+            Rel::Gt => a > b,
+            Rel::Le => a <= b,
+            Rel::Ge => a >= b,
+            Rel::Eq => a == b,
+            Rel::Ne => a != b,
+        }
+    }
+}
+
 impl Display for Rel {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
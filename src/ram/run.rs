@@ -0,0 +1,111 @@
+use std::collections::HashSet;
+use crate::ram::io_mode::IoMode;
+use crate::ram::machine::RamMachine;
+use crate::ram::types::Index;
+
+// how a single `run_with` call ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RunOutcome {
+    // a `halt` instruction was executed
+    Halted,
+    // the instruction pointer moved past the last instruction
+    FellOffEnd,
+    Error(String),
+    // the step limit of the run was reached, the machine can be resumed
+    LimitExceeded,
+    // stopped before the instruction on this line
+    Breakpoint(Index),
+    // interactive `read()` with an empty tape, push a value and run again
+    AwaitingInput,
+}
+
+impl RunOutcome {
+    // the machine stopped on its own and cannot continue
+    pub fn is_finished(&self) -> bool {
+        matches!(self, RunOutcome::Halted | RunOutcome::FellOffEnd | RunOutcome::Error(_))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RunStats {
+    // instructions executed by this run
+    pub steps: Index,
+    // instructions executed since the machine was created
+    pub instruction_count: Index,
+    pub inputs_read: Index,
+    pub outputs_written: usize,
+    pub cells_used: usize,
+}
+
+impl RunStats {
+    pub fn of(machine: &RamMachine, steps: Index) -> RunStats {
+        RunStats {
+            steps,
+            instruction_count: machine.get_instruction_count(),
+            inputs_read: machine.get_input_pointer(),
+            outputs_written: machine.get_output().len(),
+            cells_used: machine.get_memory().len(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunReport {
+    pub outcome: RunOutcome,
+    pub stats: RunStats,
+}
+
+type Observer<'a> = Box<dyn FnMut(&RamMachine) + 'a>;
+
+#[derive(Default)]
+pub struct RunOptions<'a> {
+    step_limit: Option<Index>,
+    breakpoints: HashSet<Index>,
+    observer: Option<Observer<'a>>,
+    io_mode: Option<IoMode>,
+}
+
+impl<'a> RunOptions<'a> {
+    pub fn new() -> Self {
+        RunOptions::default()
+    }
+
+    pub fn with_step_limit(mut self, limit: Index) -> Self {
+        self.step_limit = Some(limit);
+        self
+    }
+
+    pub fn with_breakpoints(mut self, breakpoints: impl IntoIterator<Item = Index>) -> Self {
+        self.breakpoints.extend(breakpoints);
+        self
+    }
+
+    // called after every executed instruction
+    pub fn with_observer(mut self, observer: impl FnMut(&RamMachine) + 'a) -> Self {
+        self.observer = Some(Box::new(observer));
+        self
+    }
+
+    pub fn with_io_mode(mut self, io_mode: IoMode) -> Self {
+        self.io_mode = Some(io_mode);
+        self
+    }
+
+    pub(crate) fn io_mode(&self) -> Option<IoMode> {
+        self.io_mode
+    }
+
+    pub(crate) fn step_limit(&self) -> Option<Index> {
+        self.step_limit
+    }
+
+    pub(crate) fn is_breakpoint(&self, index: Index) -> bool {
+        self.breakpoints.contains(&index)
+    }
+
+    pub(crate) fn observe(&mut self, machine: &RamMachine) {
+        if let Some(observer) = &mut self.observer {
+            observer(machine);
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StrictMode {
+    // uninitialized cells read as zero and any address can be used
+    #[default]
+    Off,
+    // suspicious accesses are logged and execution goes on
+    Warn,
+    // suspicious accesses stop the machine with an error
+    Error,
+}
+
+impl StrictMode {
+    pub fn next(self) -> StrictMode {
+        match self {
+            StrictMode::Off => StrictMode::Warn,
+            StrictMode::Warn => StrictMode::Error,
+            StrictMode::Error => StrictMode::Off,
+        }
+    }
+}
+
+impl Display for StrictMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StrictMode::Off => write!(f, "lenient"),
+            StrictMode::Warn => write!(f, "strict warnings"),
+            StrictMode::Error => write!(f, "strict"),
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use std::fmt::Display;
+use crate::ram::machine::RamMachine;
+use crate::ram::types::Number;
+
+// longest range a watch shows, the bounds may come from registers holding anything
+pub const MAX_RANGE: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Address {
+    // R3
+    Direct(Number),
+    // [R3]
+    Indirect(Number),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Watch {
+    // R3, [R1]
+    Cell(Address),
+    // R3..R10, [R0]..[R1], both ends included
+    Range(Address, Address),
+}
+
+impl Address {
+    pub fn parse(input: &str) -> Option<Address> {
+        let input = input.trim();
+        if let Some(inner) = input.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+            return Self::parse_register(inner).map(Address::Indirect);
+        }
+        Self::parse_register(input).map(Address::Direct)
+    }
+
+    fn parse_register(input: &str) -> Option<Number> {
+        input.trim().strip_prefix('R')?.parse().ok()
+    }
+
+    pub fn resolve(&self, machine: &RamMachine) -> Number {
+        match self {
+            Address::Direct(reg) => *reg,
+            Address::Indirect(reg) => machine.get(*reg),
+        }
+    }
+}
+
+impl Watch {
+    pub fn parse(input: &str) -> Result<Watch, String> {
+        let watch = match input.split_once("..") {
+            Some((start, end)) => Address::parse(start)
+                .zip(Address::parse(end))
+                .map(|(start, end)| Watch::Range(start, end)),
+            None => Address::parse(input).map(Watch::Cell),
+        };
+        watch.ok_or_else(|| format!("Invalid watch: {}", input.trim()))
+    }
+
+    // addresses currently covered by the watch
+    pub fn addresses(&self, machine: &RamMachine) -> Vec<Number> {
+        match self {
+            Watch::Cell(address) => vec![address.resolve(machine)],
+            Watch::Range(start, end) => {
+                let (start, end) = (start.resolve(machine), end.resolve(machine));
+                (start..=end).take(MAX_RANGE).collect()
+            }
+        }
+    }
+}
+
+impl Display for Address {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Address::Direct(reg) => write!(f, "R{}", reg),
+            Address::Indirect(reg) => write!(f, "[R{}]", reg),
+        }
+    }
+}
+
+impl Display for Watch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Watch::Cell(address) => write!(f, "{}", address),
+            Watch::Range(start, end) => write!(f, "{}..{}", start, end),
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use std::fs;
+use crate::ram::dialect::Dialect;
+use crate::ram::rasp;
+use crate::ram::types::Number;
+use crate::ui::storage;
+use crate::ui::keys::KeyBindings;
+use crate::ui::theme::Theme;
+
+const CONFIG_FILE: &str = "config";
+
+// settings read from `key = value` lines in ~/.rusty_tape/config
+#[derive(Clone)]
+pub struct Config {
+    pub paths: Vec<String>,
+    pub ignore: Vec<String>,
+    pub theme: Theme,
+    pub keys: KeyBindings,
+    pub memory_limit: Option<Number>,
+    pub dialect: Dialect,
+    pub rasp_base: Number,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            paths: Vec::new(),
+            ignore: vec![".git".to_string(), "target".to_string(), "node_modules".to_string()],
+            theme: Theme::default(),
+            keys: KeyBindings::default(),
+            memory_limit: None,
+            dialect: Dialect::default(),
+            rasp_base: rasp::DEFAULT_BASE,
+        }
+    }
+}
+
+impl Config {
+    pub fn load() -> Config {
+        let mut config = Config {
+            keys: KeyBindings::load(),
+            ..Config::default()
+        };
+        let Ok(contents) = fs::read_to_string(storage::app_dir().join(CONFIG_FILE)) else {
+            return config;
+        };
+
+        for line in contents.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            match key.trim() {
+                "paths" => config.paths = Self::parse_list(value),
+                "ignore" => config.ignore = Self::parse_list(value),
+                // unknown theme names keep the default one
+                "theme" => config.theme = Theme::by_name(value.trim()).unwrap_or_default(),
+                "memory_limit" => config.memory_limit = value.trim().parse().ok(),
+                "dialect" if value.trim() == "textbook" => config.dialect = Dialect::Textbook,
+                "rasp_base" => config.rasp_base = value.trim().parse().unwrap_or(rasp::DEFAULT_BASE),
+                _ => {}
+            }
+        }
+        config
+    }
+
+    fn parse_list(value: &str) -> Vec<String> {
+        value.split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+}
@@ -0,0 +1,94 @@
+use std::any::Any;
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::ram::machine::RamMachine;
+use crate::ram::run::{RunOptions, RunReport};
+use crate::ui::storage;
+
+// kept by the panic hook, the report is written later while unwinding
+static LAST_PANIC: Mutex<Option<String>> = Mutex::new(None);
+
+pub fn record_panic(payload: &(dyn Any + Send)) {
+    if let Ok(mut last) = LAST_PANIC.lock() {
+        *last = Some(panic_message(payload));
+    }
+}
+
+// steps or runs the machine and leaves a report behind when it panics
+pub struct CrashGuard<'a> {
+    program: &'a str,
+    machine: &'a mut RamMachine,
+}
+
+impl<'a> CrashGuard<'a> {
+    pub fn new(program: &'a str, machine: &'a mut RamMachine) -> CrashGuard<'a> {
+        CrashGuard { program, machine }
+    }
+
+    pub fn step(&mut self) -> Result<bool, String> {
+        self.machine.step()
+    }
+
+    pub fn run_with(&mut self, options: RunOptions) -> RunReport {
+        self.machine.run_with(options)
+    }
+}
+
+impl Drop for CrashGuard<'_> {
+    fn drop(&mut self) {
+        if !thread::panicking() {
+            return;
+        }
+        let message = LAST_PANIC.lock().ok()
+            .and_then(|mut last| last.take())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        match write_report(self.program, self.machine, &message) {
+            Ok(report) => eprintln!("Crash report saved to {}", report.display()),
+            Err(err) => eprintln!("Could not save a crash report: {}", err),
+        }
+    }
+}
+
+// text of a panic payload, panics carry either a &str or a String
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload.downcast_ref::<&str>().map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+// saves what is needed to reproduce a crash into ~/.rusty_tape/crash-<time>.txt
+pub fn write_report(program: &str, machine: &RamMachine, message: &str) -> Result<PathBuf, String> {
+    let time = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_secs());
+    let path = storage::app_dir().join(format!("crash-{}.txt", time));
+
+    let join = |values: &[i32]| values.iter().map(|value| value.to_string()).collect::<Vec<_>>().join(", ");
+    let instruction = machine.get_program().get(machine.get_instruction_pointer())
+        .map_or_else(|| "-".to_string(), |instruction| instruction.op.to_string());
+
+    let mut report = String::new();
+    let _ = writeln!(report, "Rusty Tape crash report");
+    let _ = writeln!(report, "program: {}", program);
+    let _ = writeln!(report, "panic: {}", message);
+    let _ = writeln!(report, "mode: {}, input: {}", machine.get_mode(), machine.get_io_mode());
+    let _ = writeln!(report, "input tape: {}", join(machine.get_input()));
+    let _ = writeln!(report, "input pointer: {}", machine.get_input_pointer());
+    let _ = writeln!(report, "output tape: {}", join(machine.get_output()));
+    let _ = writeln!(report, "instruction pointer: {}", machine.get_instruction_pointer());
+    let _ = writeln!(report, "instruction: {}", instruction);
+    let _ = writeln!(report, "instructions executed: {}", machine.get_instruction_count());
+    let _ = writeln!(report, "memory:");
+
+    let mut memory: Vec<_> = machine.get_memory().iter().collect();
+    memory.sort();
+    for (address, value) in memory {
+        let _ = writeln!(report, "  R{} = {}", address, value);
+    }
+
+    fs::create_dir_all(storage::app_dir()).map_err(|e| e.to_string())?;
+    fs::write(&path, report).map_err(|e| e.to_string())?;
+    Ok(path)
+}
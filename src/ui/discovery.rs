@@ -0,0 +1,102 @@
+use std::path::Path;
+use walkdir::{DirEntry, WalkDir};
+
+// collects `.ram` files from directories, single files and glob patterns
+pub fn scan(roots: &[String], ignore: &[String]) -> Vec<String> {
+    let mut files: Vec<String> = Vec::new();
+
+    for root in roots {
+        let root = expand_home(root);
+        if is_glob(&root) {
+            let base = glob_base(&root);
+            files.extend(walk(&base, ignore)
+                .filter(|path| glob_match(&normalize(&root), path)));
+        } else if Path::new(&root).is_file() {
+            files.push(normalize(&root));
+        } else {
+            files.extend(walk(&root, ignore));
+        }
+    }
+
+    files.sort();
+    files.dedup();
+    files
+}
+
+fn walk<'a>(root: &str, ignore: &'a [String]) -> impl Iterator<Item = String> + 'a {
+    WalkDir::new(root)
+        .into_iter()
+        .filter_entry(move |entry| entry.depth() == 0 || !is_ignored(entry, ignore))
+        .filter_map(|entry_result| entry_result.ok())
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "ram"))
+        .map(|entry| normalize(&entry.path().display().to_string()))
+}
+
+fn is_ignored(entry: &DirEntry, ignore: &[String]) -> bool {
+    entry.file_type().is_dir()
+        && ignore.iter().any(|name| entry.file_name().to_string_lossy() == name.as_str())
+}
+
+fn expand_home(path: &str) -> String {
+    match (path.strip_prefix("~/"), std::env::var("HOME")) {
+        (Some(rest), Ok(home)) => format!("{}/{}", home, rest),
+        _ => path.to_string(),
+    }
+}
+
+fn normalize(path: &str) -> String {
+    // forward slashes for display, without a leading `./` so the same file is only found once
+    let path = path.replace('\\', "/");
+    path.strip_prefix("./").unwrap_or(&path).to_string()
+}
+
+fn is_glob(path: &str) -> bool {
+    path.contains(['*', '?'])
+}
+
+fn glob_base(pattern: &str) -> String {
+    // the directories before the first wildcard are walked, the rest is matched
+    let literal: Vec<&str> = pattern.split('/')
+        .take_while(|part| !is_glob(part))
+        .collect();
+    match literal.join("/") {
+        base if base.is_empty() && pattern.starts_with('/') => "/".to_string(),
+        base if base.is_empty() => ".".to_string(),
+        base => base,
+    }
+}
+
+// `*` and `?` match within a path component, `**` matches any number of components
+pub fn glob_match(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<&str> = pattern.split('/').collect();
+    let path: Vec<&str> = path.split('/').collect();
+    match_components(&pattern, &path)
+}
+
+fn match_components(pattern: &[&str], path: &[&str]) -> bool {
+    match (pattern.first(), path.first()) {
+        (None, None) => true,
+        (Some(&"**"), _) => {
+            match_components(&pattern[1..], path)
+                || (!path.is_empty() && match_components(pattern, &path[1..]))
+        }
+        (Some(part), Some(name)) => {
+            match_component(part.as_bytes(), name.as_bytes())
+                && match_components(&pattern[1..], &path[1..])
+        }
+        _ => false,
+    }
+}
+
+fn match_component(pattern: &[u8], name: &[u8]) -> bool {
+    match (pattern.first(), name.first()) {
+        (None, None) => true,
+        (Some(b'*'), _) => {
+            match_component(&pattern[1..], name)
+                || (!name.is_empty() && match_component(pattern, &name[1..]))
+        }
+        (Some(b'?'), Some(_)) => match_component(&pattern[1..], &name[1..]),
+        (Some(a), Some(b)) => a == b && match_component(&pattern[1..], &name[1..]),
+        _ => false,
+    }
+}
@@ -0,0 +1,288 @@
+use crate::parser::Parser;
+use crate::ram::types::Index;
+use crate::ram::dialect::Dialect;
+use crate::ui::highlight;
+use crate::ui::config::Config;
+use crate::ui::keys::{Action, KeyBindings, Section};
+use crate::ui::theme::Theme;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Padding, Paragraph};
+use ratatui::Frame;
+
+pub enum EditorHandleResult {
+    Continue,
+    Exit,
+}
+
+pub struct Editor {
+    path: String,
+    lines: Vec<String>,
+    trailing_newline: bool,
+    // cursor column counts characters, not bytes
+    row: usize,
+    column: usize,
+    modified: bool,
+    confirm_exit: bool,
+    errors: Vec<(Index, String)>,
+    message: Option<String>,
+    theme: Theme,
+    keys: KeyBindings,
+    dialect: Dialect,
+}
+
+impl Editor {
+    pub fn open(path: String, config: &Config) -> Result<Editor, String> {
+        let contents = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        let mut lines: Vec<String> = contents.lines().map(str::to_string).collect();
+        if lines.is_empty() {
+            lines.push(String::new());
+        }
+
+        let mut editor = Editor {
+            path,
+            lines,
+            trailing_newline: contents.ends_with('\n'),
+            row: 0,
+            column: 0,
+            modified: false,
+            confirm_exit: false,
+            errors: Vec::new(),
+            message: None,
+            theme: config.theme,
+            keys: config.keys.clone(),
+            dialect: config.dialect,
+        };
+        editor.check();
+        Ok(editor)
+    }
+
+    pub fn draw_frame(&self, f: &mut Frame) {
+        let [code_area, status_area] = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(
+                [
+                    Constraint::Percentage(100), // Code
+                    Constraint::Min(5), // Status
+                ].as_ref())
+            .areas(f.area());
+
+        // keep the cursor line in view, the list scrolls to the selected item
+        let mut state = ListState::default().with_selected(Some(self.row));
+        f.render_stateful_widget(self.draw_code(), code_area, &mut state);
+        f.render_widget(self.draw_status(), status_area);
+    }
+
+    fn draw_code(&self) -> List<'_> {
+        let max_index_width = self.lines.len().to_string().len();
+
+        let items: Vec<ListItem> = self.lines.iter().enumerate().map(|(i, line)| {
+            let error = self.errors.iter().find(|(row, _)| *row == i).map(|(_, err)| err);
+
+            let index_span = Span::styled(
+                format!("{:width$}| ", i + 1, width = max_index_width),
+                match (error, i == self.row) {
+                    (Some(_), _) => Style::default().fg(Color::Red),
+                    (None, true) => Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+                    (None, false) => Style::default().fg(Color::DarkGray),
+                },
+            );
+
+            let mut spans = vec![index_span];
+            if i == self.row {
+                spans.extend(self.cursor_line(line));
+            } else {
+                spans.extend(highlight::highlight_line(line, &self.theme));
+            }
+
+            if let Some(error) = error {
+                spans.push(Span::styled(format!("  <- {}", error), Style::default().fg(Color::Red)));
+            }
+
+            ListItem::new(Line::from(spans))
+        }).collect();
+
+        List::new(items)
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Editing {}{}", self.path, if self.modified { " *" } else { "" }))
+                .padding(Padding::symmetric(1, 1))
+            )
+    }
+
+    fn cursor_line(&self, line: &str) -> Vec<Span<'static>> {
+        // the character under the cursor is drawn reversed
+        let byte = Self::byte_index(line, self.column);
+        let cursor_char = line[byte..].chars().next();
+        let after = byte + cursor_char.map_or(0, char::len_utf8);
+
+        let mut spans = highlight::highlight_line(&line[..byte], &self.theme);
+        spans.push(Span::styled(
+            cursor_char.unwrap_or(' ').to_string(),
+            Style::default().add_modifier(Modifier::REVERSED),
+        ));
+        spans.push(Span::raw(line[after..].to_string()));
+        spans
+    }
+
+    fn draw_status(&self) -> Paragraph<'_> {
+        let errors = match self.errors.len() {
+            0 => Span::styled("No errors", Style::default().fg(Color::Green)),
+            1 => Span::styled("1 error", Style::default().fg(Color::Red)),
+            n => Span::styled(format!("{} errors", n), Style::default().fg(Color::Red)),
+        };
+        let position = Span::raw(format!(" | line {}, column {}", self.row + 1, self.column + 1));
+        let message = self.message.as_ref()
+            .map(|message| Span::styled(format!(" | {}", message), Style::default().fg(Color::Yellow)))
+            .unwrap_or_default();
+
+        Paragraph::new(vec![
+            Line::from(vec![errors, position, message]),
+            Line::from(format!(
+                "{} to save, {} to close.",
+                self.keys.key_of(Action::Save),
+                self.keys.key_of(Action::CloseEditor),
+            ).fg(Color::DarkGray)),
+        ])
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title("Status")
+                .padding(Padding::horizontal(2))
+            )
+    }
+
+    pub fn handle_input(&mut self, key: KeyEvent) -> EditorHandleResult {
+        self.message = None;
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+        // printable keys always insert text, whatever they are bound to
+        let action = match key.code {
+            KeyCode::Char(_) if !control => None,
+            _ => self.keys.action(Section::Editor, &key),
+        };
+
+        match (action, key.code) {
+            (Some(Action::Save), _) => self.save(),
+            // leaving with unsaved changes has to be confirmed
+            (Some(Action::CloseEditor), _) if self.modified && !self.confirm_exit => {
+                self.confirm_exit = true;
+                self.message = Some(format!(
+                    "Unsaved changes, press {} again to discard them",
+                    self.keys.keys_of(Action::CloseEditor),
+                ));
+                return EditorHandleResult::Continue;
+            }
+            (Some(Action::CloseEditor), _) => return EditorHandleResult::Exit,
+            (_, KeyCode::Char(_)) if control => {}
+            (_, KeyCode::Char(c)) => self.insert(c),
+            (_, KeyCode::Tab) => (0..4).for_each(|_| self.insert(' ')),
+            (_, KeyCode::Enter) => self.split_line(),
+            (_, KeyCode::Backspace) => self.delete_back(),
+            (_, KeyCode::Delete) => self.delete_forward(),
+            (_, KeyCode::Up) => self.move_cursor(-1, 0),
+            (_, KeyCode::Down) => self.move_cursor(1, 0),
+            (_, KeyCode::PageUp) => self.move_cursor(-20, 0),
+            (_, KeyCode::PageDown) => self.move_cursor(20, 0),
+            (_, KeyCode::Left) => self.move_cursor(0, -1),
+            (_, KeyCode::Right) => self.move_cursor(0, 1),
+            (_, KeyCode::Home) => self.column = 0,
+            (_, KeyCode::End) => self.column = self.line_length(self.row),
+            _ => {}
+        }
+
+        self.confirm_exit = false;
+        EditorHandleResult::Continue
+    }
+
+    fn line_length(&self, row: usize) -> usize {
+        self.lines[row].chars().count()
+    }
+
+    fn byte_index(line: &str, column: usize) -> usize {
+        line.char_indices().nth(column).map_or(line.len(), |(i, _)| i)
+    }
+
+    fn move_cursor(&mut self, rows: i32, columns: i32) {
+        if rows != 0 {
+            self.row = (self.row as i32 + rows).clamp(0, self.lines.len() as i32 - 1) as usize;
+            self.column = self.column.min(self.line_length(self.row));
+        }
+
+        // moving past the start or end of a line wraps to the neighbouring line
+        if columns < 0 && self.column == 0 && self.row > 0 {
+            self.row -= 1;
+            self.column = self.line_length(self.row);
+        } else if columns > 0 && self.column == self.line_length(self.row) && self.row + 1 < self.lines.len() {
+            self.row += 1;
+            self.column = 0;
+        } else if columns != 0 {
+            self.column = (self.column as i32 + columns).clamp(0, self.line_length(self.row) as i32) as usize;
+        }
+    }
+
+    fn insert(&mut self, c: char) {
+        let byte = Self::byte_index(&self.lines[self.row], self.column);
+        self.lines[self.row].insert(byte, c);
+        self.column += 1;
+        self.changed();
+    }
+
+    fn split_line(&mut self) {
+        let byte = Self::byte_index(&self.lines[self.row], self.column);
+        let rest = self.lines[self.row].split_off(byte);
+        self.lines.insert(self.row + 1, rest);
+        self.row += 1;
+        self.column = 0;
+        self.changed();
+    }
+
+    fn delete_back(&mut self) {
+        if self.column > 0 {
+            self.column -= 1;
+            self.delete_forward();
+        } else if self.row > 0 {
+            let line = self.lines.remove(self.row);
+            self.row -= 1;
+            self.column = self.line_length(self.row);
+            self.lines[self.row].push_str(&line);
+            self.changed();
+        }
+    }
+
+    fn delete_forward(&mut self) {
+        if self.column < self.line_length(self.row) {
+            let byte = Self::byte_index(&self.lines[self.row], self.column);
+            self.lines[self.row].remove(byte);
+            self.changed();
+        } else if self.row + 1 < self.lines.len() {
+            let line = self.lines.remove(self.row + 1);
+            self.lines[self.row].push_str(&line);
+            self.changed();
+        }
+    }
+
+    fn changed(&mut self) {
+        self.modified = true;
+        self.check();
+    }
+
+    fn check(&mut self) {
+        self.errors = Parser::new().with_dialect(self.dialect).line_errors(&self.lines.join("\n"));
+    }
+
+    fn save(&mut self) {
+        let mut contents = self.lines.join("\n");
+        if self.trailing_newline {
+            contents.push('\n');
+        }
+
+        match std::fs::write(&self.path, contents) {
+            Ok(()) => {
+                self.modified = false;
+                self.message = Some("Saved".to_string());
+            }
+            Err(err) => self.message = Some(format!("Saving failed: {}", err)),
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use std::collections::{BTreeMap, HashSet};
+
+pub enum FileRow {
+    Folder { path: String, collapsed: bool },
+    // matched holds the positions of filter characters within the file name
+    File { index: usize, name: String, matched: Vec<usize> },
+}
+
+struct FileMatch {
+    score: i32,
+    index: usize,
+    matched: Vec<usize>,
+}
+
+// found programs shown as a tree of folders, optionally narrowed down by a fuzzy filter
+#[derive(Default)]
+pub struct FileBrowser {
+    files: Vec<String>,
+    filter: String,
+    collapsed: HashSet<String>,
+    rows: Vec<FileRow>,
+    selected_row: Option<usize>,
+}
+
+impl FileBrowser {
+    pub fn new() -> FileBrowser {
+        FileBrowser {
+            files: Vec::new(),
+            filter: String::new(),
+            collapsed: HashSet::new(),
+            rows: Vec::new(),
+            selected_row: None,
+        }
+    }
+
+    pub fn set_files(&mut self, files: Vec<String>) {
+        let selected = self.selected_path();
+        self.files = files;
+        self.rebuild(selected);
+    }
+
+    pub fn files(&self) -> &[String] {
+        &self.files
+    }
+
+    pub fn rows(&self) -> &[FileRow] {
+        &self.rows
+    }
+
+    pub fn filter(&self) -> &str {
+        &self.filter
+    }
+
+    pub fn selected_row(&self) -> Option<usize> {
+        self.selected_row
+    }
+
+    // index into files of the selected row, if it is a file
+    pub fn selected_file(&self) -> Option<usize> {
+        match self.selected_row.and_then(|row| self.rows.get(row)) {
+            Some(FileRow::File { index, .. }) => Some(*index),
+            _ => None,
+        }
+    }
+
+    pub fn push_filter(&mut self, c: char) {
+        self.filter.push(c);
+        self.rebuild(None);
+    }
+
+    pub fn pop_filter(&mut self) {
+        self.filter.pop();
+        self.rebuild(self.selected_path());
+    }
+
+    pub fn clear_filter(&mut self) {
+        let selected = self.selected_path();
+        self.filter.clear();
+        self.rebuild(selected);
+    }
+
+    pub fn select_row(&mut self, row: usize) {
+        if row < self.rows.len() {
+            self.selected_row = Some(row);
+        }
+    }
+
+    pub fn move_selection(&mut self, direction: i32) {
+        if self.rows.is_empty() {
+            return;
+        }
+        self.selected_row = Some(match self.selected_row {
+            Some(selected) => (selected as i32 + direction).clamp(0, self.rows.len() as i32 - 1) as usize,
+            None => 0,
+        });
+    }
+
+    pub fn set_collapsed(&mut self, collapsed: bool) {
+        // on a file, folding applies to the folder it is in
+        let folder = match self.selected_row.and_then(|row| self.rows.get(row)) {
+            Some(FileRow::Folder { path, .. }) => path.clone(),
+            Some(FileRow::File { index, .. }) if collapsed => Self::split(&self.files[*index]).0.to_string(),
+            _ => return,
+        };
+
+        if collapsed {
+            self.collapsed.insert(folder.clone());
+        } else {
+            self.collapsed.remove(&folder);
+        }
+        self.rebuild(None);
+        self.selected_row = self.rows.iter().position(|row| matches!(row, FileRow::Folder { path, .. } if *path == folder));
+    }
+
+    pub fn toggle_collapsed(&mut self) {
+        if let Some(FileRow::Folder { collapsed, .. }) = self.selected_row.and_then(|row| self.rows.get(row)) {
+            self.set_collapsed(!collapsed);
+        }
+    }
+
+    fn selected_path(&self) -> Option<String> {
+        self.selected_file().map(|index| self.files[index].clone())
+    }
+
+    fn rebuild(&mut self, keep_selected: Option<String>) {
+        let mut folders: BTreeMap<&str, Vec<FileMatch>> = BTreeMap::new();
+        for (index, file) in self.files.iter().enumerate() {
+            let (folder, name) = Self::split(file);
+            if let Some((score, matched)) = Self::fuzzy_match(&self.filter, name, file) {
+                folders.entry(folder).or_default().push(FileMatch { score, index, matched });
+            }
+        }
+
+        // folders with the best matches go first
+        let mut folders: Vec<(&str, Vec<FileMatch>)> = folders.into_iter().collect();
+        let filtering = !self.filter.is_empty();
+        if filtering {
+            folders.sort_by_key(|(_, files)| -files.iter().map(|file| file.score).max().unwrap_or(0));
+        }
+
+        self.rows.clear();
+        for (folder, mut files) in folders {
+            // everything matching the filter is shown, regardless of folding
+            let collapsed = !filtering && self.collapsed.contains(folder);
+            self.rows.push(FileRow::Folder { path: folder.to_string(), collapsed });
+            if collapsed {
+                continue;
+            }
+            if filtering {
+                files.sort_by_key(|file| -file.score);
+            }
+            for FileMatch { index, matched, .. } in files {
+                let name = Self::split(&self.files[index]).1.to_string();
+                self.rows.push(FileRow::File { index, name, matched });
+            }
+        }
+
+        let kept = keep_selected.and_then(|path| self.rows.iter().position(|row| {
+            matches!(row, FileRow::File { index, .. } if self.files[*index] == path)
+        }));
+        let first_file = self.rows.iter().position(|row| matches!(row, FileRow::File { .. }));
+        self.selected_row = match kept.or(first_file) {
+            Some(row) => Some(row),
+            None if self.rows.is_empty() => None,
+            None => Some(0),
+        };
+    }
+
+    fn split(file: &str) -> (&str, &str) {
+        file.rsplit_once('/').unwrap_or((".", file))
+    }
+
+    fn fuzzy_match(filter: &str, name: &str, path: &str) -> Option<(i32, Vec<usize>)> {
+        // prefer matches within the file name, fall back to the whole path
+        if let Some((score, matched)) = Self::fuzzy_score(filter, name) {
+            return Some((score + 10, matched));
+        }
+        let (score, matched) = Self::fuzzy_score(filter, path)?;
+        let name_start = path.chars().count() - name.chars().count();
+        let matched = matched.into_iter()
+            .filter(|&position| position >= name_start)
+            .map(|position| position - name_start)
+            .collect();
+        Some((score, matched))
+    }
+
+    pub fn fuzzy_score(filter: &str, candidate: &str) -> Option<(i32, Vec<usize>)> {
+        // every filter character has to appear in order, runs and word starts score higher
+        let chars: Vec<char> = candidate.chars().collect();
+        let mut matched: Vec<usize> = Vec::new();
+        let mut score = 0;
+        let mut position = 0;
+
+        for wanted in filter.chars().filter(|c| !c.is_whitespace()) {
+            while !chars.get(position)?.to_lowercase().eq(wanted.to_lowercase()) {
+                position += 1;
+            }
+            score += 1;
+            if position > 0 && matched.last() == Some(&(position - 1)) {
+                score += 5;
+            }
+            if position == 0 || matches!(chars[position - 1], '/' | '_' | '-' | '.' | ' ') {
+                score += 3;
+            }
+            matched.push(position);
+            position += 1;
+        }
+        Some((score, matched))
+    }
+}
@@ -0,0 +1,104 @@
+use crate::cfg::{BasicBlock, ControlFlowGraph, EdgeKind, Node};
+use crate::ram::types::Index;
+use crate::ui::highlight;
+use crate::ui::theme::Theme;
+use ratatui::prelude::{Color, Line, Modifier, Span, Style};
+
+// every block is drawn as a box with its labels in the top border, followed by a line of outgoing edges
+//
+// ┌─ B1 loop: ──────────────┐
+// │ if (R0 >= 4) goto end   │
+// └─────────────────────────┘
+//   yes → B3   no ↓
+pub fn lines(cfg: &ControlFlowGraph, pointer: Index, theme: &Theme) -> Vec<Line<'static>> {
+    let width = box_width(cfg);
+    let current_style = Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD);
+    let dim = Style::default().fg(Color::DarkGray);
+
+    let mut lines = Vec::new();
+    for (id, block) in cfg.blocks.iter().enumerate() {
+        let current = block.instructions.contains(&pointer);
+        let border = if current { current_style } else { dim };
+
+        let title = title(cfg, id);
+        let fill = width.saturating_sub(title.chars().count() + 3);
+        lines.push(Line::from(vec![
+            Span::styled("┌─", border),
+            Span::styled(title, if current { current_style } else { theme.label }),
+            Span::styled(format!("{}┐", "─".repeat(fill)), border),
+        ]));
+
+        for (&index, code) in block.instructions.iter().zip(&block.code) {
+            let padding = " ".repeat(width.saturating_sub(code.chars().count() + 4));
+            let mut spans = vec![Span::styled("│ ", border)];
+            if index == pointer {
+                spans.push(Span::styled(code.clone(), current_style));
+            } else {
+                spans.extend(highlight::highlight_line(code, theme));
+            }
+            spans.push(Span::styled(format!("{} │", padding), border));
+            lines.push(Line::from(spans));
+        }
+
+        lines.push(Line::styled(format!("└{}┘", "─".repeat(width.saturating_sub(2))), border));
+        lines.push(Line::styled(edges(cfg, id), dim));
+    }
+    lines
+}
+
+// row of the line showing the instruction, one past the last row when no block holds it
+pub fn row_of(cfg: &ControlFlowGraph, index: Index) -> usize {
+    let mut row = 0;
+    for block in &cfg.blocks {
+        if let Some(position) = block.instructions.iter().position(|&i| i == index) {
+            return row + 1 + position;
+        }
+        row += block_height(block);
+    }
+    row
+}
+
+pub fn height(cfg: &ControlFlowGraph) -> usize {
+    cfg.blocks.iter().map(block_height).sum()
+}
+
+// two borders and the edge line around the code
+fn block_height(block: &BasicBlock) -> usize {
+    block.instructions.len() + 3
+}
+
+// ` B1 loop: `
+fn title(cfg: &ControlFlowGraph, id: usize) -> String {
+    let labels: String = cfg.blocks[id].labels.iter().map(|label| format!("{}: ", label)).collect();
+    format!(" B{} {}", id, labels)
+}
+
+// all boxes share the width of the widest one, so the chart reads as a single column
+fn box_width(cfg: &ControlFlowGraph) -> usize {
+    cfg.blocks.iter().enumerate()
+        .flat_map(|(id, block)| {
+            let code = block.code.iter().map(|code| code.chars().count() + 4);
+            code.chain([title(cfg, id).chars().count() + 4])
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+// `yes → B3   no ↓`, an arrow down leads to the block right below
+fn edges(cfg: &ControlFlowGraph, id: usize) -> String {
+    let edges: Vec<String> = cfg.edges_from(id)
+        .map(|edge| {
+            let arrow = match edge.to {
+                Node::Block(to) if to == id + 1 => "↓".to_string(),
+                Node::Block(to) => format!("→ B{}", to),
+                Node::End => "→ end".to_string(),
+            };
+            match edge.kind {
+                EdgeKind::Always => arrow,
+                EdgeKind::Taken => format!("yes {}", arrow),
+                EdgeKind::NotTaken => format!("no {}", arrow),
+            }
+        })
+        .collect();
+    format!("  {}", edges.join("   "))
+}
@@ -1,16 +1,21 @@
 use crate::ui::simulation::{SimulationHandleResult, Simulation};
 use ratatui::backend::CrosstermBackend;
-use ratatui::crossterm::{
-    event::{self, Event},
-    execute, terminal::{disable_raw_mode, enable_raw_mode},
-};
+use ratatui::crossterm::event::{self, Event};
 use ratatui::Terminal;
-use ratatui::crossterm::event::KeyEventKind;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
 use std::io;
+use crate::ui::editor::{Editor, EditorHandleResult};
 use crate::ui::menu::{Menu, MenuHandleResult};
+use crate::ui::config::Config;
+use crate::ui::keys::{Action, Section};
+use crate::ui::help;
+use crate::ui::terminal::TerminalGuard;
 
 pub struct UiHandler {
     simulation: Option<Simulation>,
+    editor: Option<Editor>,
+    config: Config,
+    show_help: bool,
     menu: Menu
 }
 
@@ -22,39 +27,84 @@ impl Default for UiHandler {
 
 impl UiHandler {
     pub fn new() -> Self {
+        Self::with_paths(Vec::new())
+    }
+
+    // directories, files or glob patterns to look for programs in
+    pub fn with_paths(paths: Vec<String>) -> Self {
+        let config = Config::load();
         UiHandler {
             simulation: None,
-            menu: Menu::new()
+            editor: None,
+            menu: Menu::new(paths, &config),
+            config,
+            show_help: false,
         }
     }
     
     pub fn run(&mut self) -> io::Result<()> {
-        // Setup terminal
-        enable_raw_mode()?;
-        let mut stdout = io::stdout();
-        execute!(stdout, ratatui::crossterm::terminal::EnterAlternateScreen)?;
-        let backend = CrosstermBackend::new(stdout);
+        // Setup terminal, it is restored when the guard drops even after an error or a panic
+        let _guard = TerminalGuard::enter()?;
+        let backend = CrosstermBackend::new(io::stdout());
         let mut terminal = Terminal::new(backend)?;
         
         // Main loop
         let err: Result<(), String> = loop {
             // Draw frame
             terminal.draw(|f| {
-                if let Some(simulation) = &self.simulation { 
+                if let Some(editor) = &self.editor {
+                    editor.draw_frame(f)
+                } else if let Some(simulation) = &self.simulation {
                     simulation.draw_frame(f) 
                 } else {
                     self.menu.draw_frame(f)
                 }
+                if self.show_help {
+                    help::draw_overlay(f, self.help_section(), &self.config.keys);
+                }
             })?;
 
             // Handle input events
-            if let Event::Key(key) = event::read()? {
+            let event = event::read()?;
+            if let Event::Mouse(mouse) = event {
+                if self.editor.is_some() {
+                    continue;
+                } else if let Some(ref mut simulation) = self.simulation {
+                    simulation.handle_mouse(mouse);
+                } else {
+                    self.menu.handle_mouse(mouse);
+                }
+                continue;
+            }
+            if let Event::Key(key) = event {
                 if key.kind != KeyEventKind::Press {
                     continue;
                 }
-                if let Some(ref mut simulation) = self.simulation { 
+                // the overlay swallows keys until it is closed
+                if self.show_help {
+                    self.show_help = false;
+                    continue;
+                }
+                if self.config.keys.action(Section::General, &key) == Some(Action::Help) && self.allows_help(&key) {
+                    self.show_help = true;
+                    continue;
+                }
+                if let Some(ref mut editor) = self.editor {
+                    match editor.handle_input(key) {
+                        EditorHandleResult::Continue => continue,
+                        EditorHandleResult::Exit => {
+                            self.editor = None;
+                            continue;
+                        },
+                    }
+                } else if let Some(ref mut simulation) = self.simulation { 
                     match simulation.handle_input(key) {
                         SimulationHandleResult::Continue => continue,
+                        SimulationHandleResult::Edit(path) => {
+                            // the simulation is outdated once the program is edited
+                            self.simulation = None;
+                            self.open_editor(path);
+                        },
                         SimulationHandleResult::Exit => {
                             self.simulation = None;
                             continue;
@@ -64,23 +114,46 @@ impl UiHandler {
                     match self.menu.handle_input(key) {
                         MenuHandleResult::Continue => continue,
                         MenuHandleResult::Exit => break Ok(()),
-                        MenuHandleResult::Machine(name, machine) => { 
-                            self.simulation = Some(Simulation::new(name, machine));
+                        MenuHandleResult::Machine(path, machine) => { 
+                            self.simulation = Some(Simulation::new(path, *machine, &self.config));
                         },
+                        MenuHandleResult::Edit(path) => self.open_editor(path),
                     }
                 }
             }
         };
 
-        // Restore terminal
-        disable_raw_mode()?;
-        execute!(
-            terminal.backend_mut(),
-            ratatui::crossterm::terminal::LeaveAlternateScreen
-        )?;
         match err {
             Ok(_) => Ok(()),
-            Err(e) => Err(io::Error::new(io::ErrorKind::Other, e)),
+            Err(e) => Err(io::Error::other(e)),
+        }
+    }
+
+    // screen whose actions are listed in the help overlay
+    fn help_section(&self) -> Section {
+        if self.editor.is_some() {
+            Section::Editor
+        } else if self.simulation.is_some() {
+            Section::Simulation
+        } else {
+            self.menu.help_section()
+        }
+    }
+
+    // printable keys stay text while typing, so they only open the help where nothing is typed
+    fn allows_help(&self, key: &KeyEvent) -> bool {
+        let typing = match &self.simulation {
+            _ if self.editor.is_some() => true,
+            Some(simulation) => simulation.is_typing(),
+            None => self.menu.is_typing(),
+        };
+        !typing || !matches!(key.code, KeyCode::Char(_))
+    }
+
+    fn open_editor(&mut self, path: String) {
+        match Editor::open(path, &self.config) {
+            Ok(editor) => self.editor = Some(editor),
+            Err(err) => self.menu.set_error(err),
         }
     }
 }
@@ -0,0 +1,45 @@
+use crate::ui::keys::{Action, KeyBindings, Section};
+use ratatui::layout::{Constraint, Flex, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, Padding};
+use ratatui::Frame;
+
+// lists the actions of the current screen with their keys on top of it
+pub fn draw_overlay(f: &mut Frame, section: Section, keys: &KeyBindings) {
+    let actions: Vec<(String, &str)> = Action::ALL.iter()
+        .filter(|action| action.section() == section || action.section() == Section::General)
+        .map(|action| (keys.keys_of(*action), action.description()))
+        .collect();
+    let keys_width = actions.iter().map(|(keys, _)| keys.len()).max().unwrap_or(0);
+
+    let items: Vec<ListItem> = actions.iter()
+        .map(|(keys, description)| ListItem::new(Line::from(vec![
+            Span::styled(
+                format!("{:width$}  ", keys, width = keys_width),
+                Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(description.to_string()),
+        ])))
+        .collect();
+
+    let width = actions.iter().map(|(_, description)| description.len()).max().unwrap_or(0) + keys_width + 8;
+    let area = centered(f.area(), width as u16, items.len() as u16 + 4);
+
+    let list = List::new(items)
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title(format!("Help - {:?}", section))
+            .title_bottom("Press any key to close")
+            .padding(Padding::symmetric(2, 1))
+        );
+
+    f.render_widget(Clear, area);
+    f.render_widget(list, area);
+}
+
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let [area] = Layout::horizontal([Constraint::Length(width)]).flex(Flex::Center).areas(area);
+    let [area] = Layout::vertical([Constraint::Length(height)]).flex(Flex::Center).areas(area);
+    area
+}
@@ -0,0 +1,66 @@
+use crate::lexer::{self, Token, TokenKind};
+use crate::ui::theme::Theme;
+use ratatui::style::Style;
+use ratatui::text::Span;
+
+// styles every token of a source line, shared by the menu, simulation and editor code panes
+pub fn highlight_line(line: &str, theme: &Theme) -> Vec<Span<'static>> {
+    let tokens = lexer::tokenize(line);
+    let significant: Vec<Token> = tokens.iter()
+        .filter(|token| token.kind != TokenKind::Whitespace)
+        .copied()
+        .collect();
+
+    let labels = label_positions(&significant);
+
+    let mut spans = Vec::with_capacity(tokens.len());
+    let mut position: usize = 0;
+    for token in &tokens {
+        let style = if token.kind == TokenKind::Whitespace {
+            // the spaces inside a multi-word label belong to it
+            let inside_label = position > 0 && labels[position - 1] && labels.get(position).copied().unwrap_or(false);
+            if inside_label { theme.label } else { Style::default() }
+        } else {
+            position += 1;
+            if labels[position - 1] { theme.label } else { token_style(token, theme) }
+        };
+        spans.push(Span::styled(token.text.to_string(), style));
+    }
+    spans
+}
+
+// labels are the words before a colon (the colon included) at the start of the line and after goto,
+// following the same rule as the parser
+fn label_positions(tokens: &[Token]) -> Vec<bool> {
+    let mut labels = vec![false; tokens.len()];
+
+    let mut start = 0;
+    loop {
+        let length = lexer::label_length(&tokens[start..]);
+        if length == 0 || tokens.get(start + length).is_none_or(|token| token.kind != TokenKind::Colon) {
+            break;
+        }
+        labels[start..=start + length].fill(true);
+        start += length + 1;
+    }
+
+    for (i, token) in tokens.iter().enumerate() {
+        if token.kind == TokenKind::Keyword && token.text == "goto" {
+            let length = lexer::label_length(&tokens[i + 1..]);
+            labels[i + 1..i + 1 + length].fill(true);
+        }
+    }
+    labels
+}
+
+fn token_style(token: &Token, theme: &Theme) -> Style {
+    match token.kind {
+        TokenKind::Keyword => theme.keyword,
+        TokenKind::Register => theme.register,
+        TokenKind::Number => theme.constant,
+        TokenKind::Assign | TokenKind::Operator | TokenKind::Relation => theme.operator,
+        TokenKind::Comment => theme.comment,
+        TokenKind::Unknown => theme.invalid,
+        _ => Style::default(),
+    }
+}
@@ -0,0 +1,342 @@
+use std::collections::HashMap;
+use std::fs;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crate::ui::storage;
+
+const KEYS_FILE: &str = "keys";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Section {
+    General,
+    Files,
+    Input,
+    Simulation,
+    Editor,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Help,
+    // file list
+    SelectPrevious,
+    SelectNext,
+    FoldFolder,
+    UnfoldFolder,
+    OpenFile,
+    EditFile,
+    Rescan,
+    Exit,
+    // input tape
+    PreviousInput,
+    NextInput,
+    ToggleRasp,
+    ToggleInteractive,
+    ToggleStrict,
+    SavePreset,
+    StartSimulation,
+    BackToFiles,
+    // simulation
+    Step,
+    Continue,
+    ScrollMemoryUp,
+    ScrollMemoryDown,
+    ScrollCodeUp,
+    ScrollCodeDown,
+    FollowPointer,
+    GoToLabel,
+    ToggleExpansion,
+    ToggleFlowchart,
+    AddWatch,
+    RemoveWatch,
+    EditProgram,
+    StopSimulation,
+    // editor
+    Save,
+    CloseEditor,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyBinding {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+// actions and their keys, the defaults can be overridden by `action = key, key` lines in ~/.rusty_tape/keys
+#[derive(Clone)]
+pub struct KeyBindings {
+    bindings: HashMap<Action, Vec<KeyBinding>>,
+}
+
+impl Action {
+    pub const ALL: [Action; 33] = [
+        Action::Help,
+        Action::SelectPrevious, Action::SelectNext, Action::FoldFolder, Action::UnfoldFolder,
+        Action::OpenFile, Action::EditFile, Action::Rescan, Action::Exit,
+        Action::PreviousInput, Action::NextInput, Action::ToggleRasp, Action::ToggleInteractive,
+        Action::ToggleStrict, Action::SavePreset, Action::StartSimulation, Action::BackToFiles,
+        Action::Step, Action::Continue, Action::ScrollMemoryUp, Action::ScrollMemoryDown,
+        Action::ScrollCodeUp, Action::ScrollCodeDown, Action::FollowPointer, Action::GoToLabel,
+        Action::ToggleExpansion, Action::ToggleFlowchart, Action::AddWatch, Action::RemoveWatch, Action::EditProgram, Action::StopSimulation,
+        Action::Save, Action::CloseEditor,
+    ];
+
+    // name used in the keys file
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Help => "help",
+            Action::SelectPrevious => "select_previous",
+            Action::SelectNext => "select_next",
+            Action::FoldFolder => "fold_folder",
+            Action::UnfoldFolder => "unfold_folder",
+            Action::OpenFile => "open_file",
+            Action::EditFile => "edit_file",
+            Action::Rescan => "rescan",
+            Action::Exit => "exit",
+            Action::PreviousInput => "previous_input",
+            Action::NextInput => "next_input",
+            Action::ToggleRasp => "toggle_rasp",
+            Action::ToggleInteractive => "toggle_interactive",
+            Action::ToggleStrict => "toggle_strict",
+            Action::SavePreset => "save_preset",
+            Action::StartSimulation => "start_simulation",
+            Action::BackToFiles => "back_to_files",
+            Action::Step => "step",
+            Action::Continue => "continue",
+            Action::ScrollMemoryUp => "scroll_memory_up",
+            Action::ScrollMemoryDown => "scroll_memory_down",
+            Action::ScrollCodeUp => "scroll_code_up",
+            Action::ScrollCodeDown => "scroll_code_down",
+            Action::FollowPointer => "follow_pointer",
+            Action::GoToLabel => "go_to_label",
+            Action::ToggleExpansion => "toggle_expansion",
+            Action::ToggleFlowchart => "toggle_flowchart",
+            Action::AddWatch => "add_watch",
+            Action::RemoveWatch => "remove_watch",
+            Action::EditProgram => "edit_program",
+            Action::StopSimulation => "stop_simulation",
+            Action::Save => "save",
+            Action::CloseEditor => "close_editor",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::Help => "Show or hide this help",
+            Action::SelectPrevious => "Select the previous file",
+            Action::SelectNext => "Select the next file",
+            Action::FoldFolder => "Fold the folder",
+            Action::UnfoldFolder => "Unfold the folder",
+            Action::OpenFile => "Load the file or toggle the folder",
+            Action::EditFile => "Edit the file",
+            Action::Rescan => "Rescan the program directories",
+            Action::Exit => "Clear the filter, then exit",
+            Action::PreviousInput => "Previous saved input",
+            Action::NextInput => "Next saved input",
+            Action::ToggleRasp => "Toggle RASP mode",
+            Action::ToggleInteractive => "Toggle interactive input",
+            Action::ToggleStrict => "Cycle the strict mode",
+            Action::SavePreset => "Save the input as a preset",
+            Action::StartSimulation => "Start the simulation",
+            Action::BackToFiles => "Back to the file list",
+            Action::Step => "Execute one instruction",
+            Action::Continue => "Run to the next breakpoint",
+            Action::ScrollMemoryUp => "Scroll the memory up",
+            Action::ScrollMemoryDown => "Scroll the memory down",
+            Action::ScrollCodeUp => "Scroll the code up a page",
+            Action::ScrollCodeDown => "Scroll the code down a page",
+            Action::FollowPointer => "Follow the instruction pointer",
+            Action::GoToLabel => "Scroll the code to a label",
+            Action::ToggleExpansion => "Show the lowered instructions of expressions",
+            Action::ToggleFlowchart => "Show the code as a flowchart of basic blocks",
+            Action::AddWatch => "Add a watch",
+            Action::RemoveWatch => "Remove the last watch",
+            Action::EditProgram => "Edit the program once finished",
+            Action::StopSimulation => "Back to the menu",
+            Action::Save => "Save the file",
+            Action::CloseEditor => "Close the editor",
+        }
+    }
+
+    pub fn section(&self) -> Section {
+        match self {
+            Action::Help => Section::General,
+            Action::SelectPrevious | Action::SelectNext | Action::FoldFolder | Action::UnfoldFolder
+            | Action::OpenFile | Action::EditFile | Action::Rescan | Action::Exit => Section::Files,
+            Action::PreviousInput | Action::NextInput | Action::ToggleRasp | Action::ToggleInteractive
+            | Action::ToggleStrict | Action::SavePreset | Action::StartSimulation | Action::BackToFiles => Section::Input,
+            Action::Save | Action::CloseEditor => Section::Editor,
+            _ => Section::Simulation,
+        }
+    }
+
+    fn default_keys(&self) -> &'static str {
+        match self {
+            Action::Help => "?, F1",
+            Action::SelectPrevious | Action::PreviousInput | Action::ScrollMemoryUp => "Up",
+            Action::SelectNext | Action::NextInput | Action::ScrollMemoryDown => "Down",
+            Action::FoldFolder => "Left",
+            Action::UnfoldFolder => "Right",
+            Action::OpenFile | Action::StartSimulation => "Enter",
+            Action::EditFile => "Ctrl+E",
+            Action::Rescan => "F5, Ctrl+R",
+            Action::Exit | Action::BackToFiles | Action::StopSimulation | Action::CloseEditor => "Esc",
+            Action::ToggleRasp => "r, R",
+            Action::ToggleInteractive => "i, I",
+            Action::ToggleStrict => "s, S",
+            Action::SavePreset | Action::Save => "Ctrl+S",
+            Action::Step => "Space, Enter",
+            Action::Continue => "c",
+            Action::ScrollCodeUp => "PageUp",
+            Action::ScrollCodeDown => "PageDown",
+            Action::FollowPointer => "Home",
+            Action::GoToLabel => "g",
+            Action::ToggleExpansion => "x",
+            Action::ToggleFlowchart => "f",
+            Action::AddWatch => "w",
+            Action::RemoveWatch => "W",
+            Action::EditProgram => "e, E",
+        }
+    }
+}
+
+impl KeyBinding {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> KeyBinding {
+        KeyBinding { code, modifiers }
+    }
+
+    // `Ctrl+S`, `F5`, `PageUp`, `Space`, `w`, ...
+    pub fn parse(input: &str) -> Option<KeyBinding> {
+        let input = input.trim();
+        let mut modifiers = KeyModifiers::NONE;
+        let mut key = input;
+        while let Some((modifier, rest)) = key.split_once('+').filter(|(_, rest)| !rest.is_empty()) {
+            modifiers |= match modifier.to_lowercase().as_str() {
+                "ctrl" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return None,
+            };
+            key = rest;
+        }
+
+        let code = match key.to_lowercase().as_str() {
+            "enter" => KeyCode::Enter,
+            "esc" => KeyCode::Esc,
+            "space" => KeyCode::Char(' '),
+            "comma" => KeyCode::Char(','),
+            "tab" => KeyCode::Tab,
+            "backspace" => KeyCode::Backspace,
+            "delete" => KeyCode::Delete,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            name if name.len() > 1 && name.starts_with('f') => KeyCode::F(name[1..].parse().ok()?),
+            _ => {
+                let mut chars = key.chars();
+                let c = chars.next()?;
+                if chars.next().is_some() {
+                    return None;
+                }
+                // terminals report Ctrl+S as a lowercase s and Shift+w as W
+                if modifiers.contains(KeyModifiers::SHIFT) {
+                    modifiers -= KeyModifiers::SHIFT;
+                    KeyCode::Char(c.to_ascii_uppercase())
+                } else if modifiers.is_empty() {
+                    KeyCode::Char(c)
+                } else {
+                    KeyCode::Char(c.to_ascii_lowercase())
+                }
+            }
+        };
+        Some(KeyBinding { code, modifiers })
+    }
+
+    fn matches(&self, key: &KeyEvent) -> bool {
+        // shift is already part of the character for printable keys
+        let modifiers = match key.code {
+            KeyCode::Char(_) => key.modifiers - KeyModifiers::SHIFT,
+            _ => key.modifiers,
+        };
+        self.code == key.code && self.modifiers == modifiers
+    }
+}
+
+impl std::fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (modifier, name) in [(KeyModifiers::CONTROL, "Ctrl"), (KeyModifiers::ALT, "Alt"), (KeyModifiers::SHIFT, "Shift")] {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{}+", name)?;
+            }
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(',') => write!(f, "Comma"),
+            KeyCode::Char(c) if self.modifiers.is_empty() => write!(f, "{}", c),
+            KeyCode::Char(c) => write!(f, "{}", c.to_ascii_uppercase()),
+            KeyCode::PageUp => write!(f, "PageUp"),
+            KeyCode::PageDown => write!(f, "PageDown"),
+            code => write!(f, "{}", code),
+        }
+    }
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        let bindings = Action::ALL.iter()
+            .map(|action| (*action, Self::parse_list(action.default_keys())))
+            .collect();
+        KeyBindings { bindings }
+    }
+}
+
+impl KeyBindings {
+    pub fn load() -> KeyBindings {
+        let mut keys = KeyBindings::default();
+        let Ok(contents) = fs::read_to_string(storage::app_dir().join(KEYS_FILE)) else {
+            return keys;
+        };
+
+        for line in contents.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((name, value)) = line.split_once('=') else {
+                continue;
+            };
+            if let Some(action) = Action::ALL.iter().find(|action| action.name() == name.trim()) {
+                keys.bindings.insert(*action, Self::parse_list(value));
+            }
+        }
+        keys
+    }
+
+    pub fn parse_list(value: &str) -> Vec<KeyBinding> {
+        value.split(',').filter_map(KeyBinding::parse).collect()
+    }
+
+    // the action of the given section, or of the general one, bound to the key
+    pub fn action(&self, section: Section, key: &KeyEvent) -> Option<Action> {
+        Action::ALL.iter()
+            .filter(|action| action.section() == section || action.section() == Section::General)
+            .find(|action| self.bindings.get(action).is_some_and(|keys| keys.iter().any(|binding| binding.matches(key))))
+            .copied()
+    }
+
+    // the first key of the action, for short hints
+    pub fn key_of(&self, action: Action) -> String {
+        self.bindings.get(&action)
+            .and_then(|keys| keys.first())
+            .map_or_else(|| "-".to_string(), |key| key.to_string())
+    }
+
+    pub fn keys_of(&self, action: Action) -> String {
+        self.bindings.get(&action)
+            .map(|keys| keys.iter().map(|key| key.to_string()).collect::<Vec<_>>().join(", "))
+            .unwrap_or_default()
+    }
+}
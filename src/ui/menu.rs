@@ -1,15 +1,29 @@
 use crate::parser::Parser;
+use crate::ram::dialect::Dialect;
+use crate::ram::execution_mode::ExecutionMode;
+use crate::ram::io_mode::IoMode;
+use crate::ram::strict_mode::StrictMode;
+use crate::ram::types::Number;
 use crate::ram::machine::RamMachine;
-use ratatui::crossterm::event::{KeyCode, KeyEvent};
-use ratatui::layout::{Constraint, Direction, Layout};
-use ratatui::style::{Color, Style, Stylize};
+use crate::ui::config::Config;
+use crate::ui::discovery;
+use crate::ui::file_browser::{FileBrowser, FileRow};
+use crate::ui::highlight;
+use crate::ui::keys::{Action, KeyBindings, Section};
+use crate::ui::session::{self, InputHistory};
+use crate::ui::theme::Theme;
+use std::cell::Cell;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::{Constraint, Direction, Layout, Position, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
 use ratatui::text::{Line, Span, Text};
-use ratatui::widgets::{Block, Borders, List, ListItem, Padding, Paragraph};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Padding, Paragraph};
 use ratatui::Frame;
 
 pub enum MenuHandleResult {
     Continue,
-    Machine(String, RamMachine),
+    Machine(String, Box<RamMachine>),
+    Edit(String),
     Exit,
 }
 
@@ -17,20 +31,44 @@ pub enum MenuHandleResult {
 pub enum MenuState {
     SelectingFile,
     SpecifyingInput,
+    NamingPreset,
+}
+
+// a previously used input tape offered in the input prompt
+struct SavedInput {
+    preset: Option<String>,
+    tape: String,
 }
 
 pub struct Menu {
     logo: String,
     input: String,
     error: Option<String>,
-    found_files: Vec<String>,
+    roots: Vec<String>,
+    ignore: Vec<String>,
+    browser: FileBrowser,
     state: MenuState,
-    selected_file: Option<usize>,
     selected_machine: Option<RamMachine>,
+    mode: ExecutionMode,
+    io_mode: IoMode,
+    strict_mode: StrictMode,
+    memory_limit: Option<Number>,
+    rasp_base: Number,
+    dialect: Dialect,
+    history: InputHistory,
+    saved_inputs: Vec<SavedInput>,
+    saved_input_index: Option<usize>,
+    draft_input: String,
+    preset_name: String,
+    theme: Theme,
+    keys: KeyBindings,
+    // where the file list was drawn and how far it was scrolled, for mouse clicks
+    file_list_area: Cell<Rect>,
+    file_list_offset: Cell<usize>,
 }
 
 impl Menu {
-    pub fn new() -> Menu {
+    pub fn new(paths: Vec<String>, config: &Config) -> Menu {
         let logo = r#"
     ____             __           ______               
    / __ \__  _______/ /___  __   /_  __/___ _____  ___ 
@@ -45,11 +83,30 @@ impl Menu {
             logo,
             input: String::new(),
             error: None,
-            found_files: Vec::new(),
+            roots: Vec::new(),
+            ignore: Vec::new(),
+            browser: FileBrowser::new(),
             state: MenuState::SelectingFile,
-            selected_file: None,
             selected_machine: None,
+            mode: ExecutionMode::Ram,
+            io_mode: IoMode::Tape,
+            strict_mode: StrictMode::Off,
+            memory_limit: config.memory_limit,
+            rasp_base: config.rasp_base,
+            dialect: config.dialect,
+            history: InputHistory::load(),
+            saved_inputs: Vec::new(),
+            saved_input_index: None,
+            draft_input: String::new(),
+            preset_name: String::new(),
+            theme: config.theme,
+            keys: config.keys.clone(),
+            file_list_area: Cell::new(Rect::default()),
+            file_list_offset: Cell::new(0),
         };
+        // directories given on the command line take precedence over the config file
+        menu.roots = if paths.is_empty() { config.paths.clone() } else { paths };
+        menu.ignore = config.ignore.clone();
         menu.scan_for_files();
         menu
     }
@@ -69,7 +126,7 @@ impl Menu {
             .direction(Direction::Vertical)
             .constraints(
                 [
-                    Constraint::Length(15), // Title
+                    Constraint::Length(16), // Title
                     Constraint::Min(0), // Table
                 ].as_ref())
             .areas(left);
@@ -91,7 +148,12 @@ impl Menu {
 
         // Draw widgets
         f.render_widget(self.draw_title(), title_area);
-        f.render_widget(self.draw_file_list(), table_area);
+        let mut file_list_state = ListState::default()
+            .with_offset(self.file_list_offset.get())
+            .with_selected(self.browser.selected_row());
+        f.render_stateful_widget(self.draw_file_list(), table_area, &mut file_list_state);
+        self.file_list_area.set(table_area);
+        self.file_list_offset.set(file_list_state.offset());
         f.render_widget(self.draw_code(), code_area);
         if self.error.is_some() {
             f.render_widget(self.draw_error(), error_area);
@@ -99,7 +161,7 @@ impl Menu {
         f.render_widget(self.draw_input(), input_area);
     }
 
-    pub fn draw_title(&self) -> List {
+    pub fn draw_title(&self) -> List<'_> {
         let indent = " ";
 
         let title_widget = ListItem::new(Text::from(self.logo.clone()).centered().fg(Color::Yellow));
@@ -107,16 +169,27 @@ impl Menu {
         let greeting = "Welcome to Rusty Tape, my own Rust-powered\n RAM machine simulator!";
         let greeting_widget = ListItem::new(Text::from(format!("{}{}", indent, greeting)));
 
+        let key = |action| self.keys.key_of(action);
         let info_widgets = match self.state {
             MenuState::SelectingFile => vec![
-                "Up/Down arrows to select a file.",
-                "Enter to load the file.",
-                "Esc to exit.",
+                format!("{}/{} to select a file.", key(Action::SelectPrevious), key(Action::SelectNext)),
+                format!("Type to filter, {}/{} to fold folders.", key(Action::FoldFolder), key(Action::UnfoldFolder)),
+                format!("{} to load the file, {} to edit it.", key(Action::OpenFile), key(Action::EditFile)),
+                format!("{} to rescan.", key(Action::Rescan)),
+                format!("{} to exit, {} for help.", key(Action::Exit), key(Action::Help)),
             ],
             MenuState::SpecifyingInput => vec![
-                "Please enter the input tape.",
-                "Enter to confirm.",
-                "Esc to go back.",
+                "Please enter the input tape.".to_string(),
+                format!("{}/{} to browse saved inputs.", key(Action::PreviousInput), key(Action::NextInput)),
+                format!("{} to toggle RASP, {} to toggle interactive.", key(Action::ToggleRasp), key(Action::ToggleInteractive)),
+                format!("{} to cycle the strict mode.", key(Action::ToggleStrict)),
+                format!("{} to save as preset.", key(Action::SavePreset)),
+                format!("{} to confirm, {} to go back.", key(Action::StartSimulation), key(Action::BackToFiles)),
+            ],
+            MenuState::NamingPreset => vec![
+                "Please enter the preset name.".to_string(),
+                format!("{} to save.", key(Action::StartSimulation)),
+                format!("{} to cancel.", key(Action::BackToFiles)),
             ],
         }.into_iter()
             .map(|line| format!("{}{}", indent, line))
@@ -130,62 +203,77 @@ impl Menu {
             .block(Block::default().borders(Borders::NONE).padding(Padding::symmetric(1, 0)))
     }
 
-    fn draw_file_list(&self) -> List {
+    fn draw_file_list(&self) -> List<'_> {
         let border_color = match self.state {
             MenuState::SelectingFile => Color::White,
-            MenuState::SpecifyingInput => Color::DarkGray,
+            MenuState::SpecifyingInput | MenuState::NamingPreset => Color::DarkGray,
         };
 
-        let items: Vec<ListItem> = self.found_files.iter().enumerate().map(|(i, file)| {
-            let (arrow, style) = if Some(i) == self.selected_file {
+        let items: Vec<ListItem> = self.browser.rows().iter().enumerate().map(|(i, row)| {
+            let (arrow, style) = if Some(i) == self.browser.selected_row() {
                 ("> ", Style::default().fg(Color::Yellow))
             } else {
                 ("  ", Style::default())
             };
-
-            let path_parts: Vec<&str> = file.split('/').collect();
-            let (dir_path, filename) = if path_parts.len() > 1 {
-                (path_parts[..path_parts.len() - 1].join("/") + "/", path_parts[path_parts.len() - 1])
-            } else {
-                ("".to_string(), file.as_str())
-            };
-
             let arrow_span = Span::styled(arrow, style);
-            let dir_span = Span::styled(dir_path, Style::default().fg(Color::DarkGray));
-            let file_span = Span::styled(filename, style);
 
-            ListItem::new(Line::from(vec![arrow_span, dir_span, file_span]))
+            match row {
+                FileRow::Folder { path, collapsed } => {
+                    let fold_span = Span::styled(
+                        if *collapsed { "+ " } else { "- " },
+                        Style::default().fg(Color::DarkGray),
+                    );
+                    let dir_span = Span::styled(format!("{}/", path), style.fg(Color::DarkGray));
+                    ListItem::new(Line::from(vec![arrow_span, fold_span, dir_span]))
+                }
+                FileRow::File { name, matched, .. } => {
+                    // highlight the characters matched by the filter
+                    let mut spans = vec![arrow_span, Span::raw("  ")];
+                    spans.extend(name.chars().enumerate().map(|(position, c)| {
+                        if matched.contains(&position) {
+                            Span::styled(c.to_string(), style.add_modifier(Modifier::BOLD).fg(Color::Cyan))
+                        } else {
+                            Span::styled(c.to_string(), style)
+                        }
+                    }));
+                    ListItem::new(Line::from(spans))
+                }
+            }
         }).collect();
 
+        let title = if self.browser.filter().is_empty() {
+            "Files".to_string()
+        } else {
+            format!("Files - filter: {}", self.browser.filter())
+        };
+
         List::new(items)
             .block(Block::default()
                 .borders(Borders::ALL)
                 .fg(border_color)
-                .title("Files")
+                .title(title)
                 .padding(Padding::symmetric(1, 1))
             )
     }
 
-    fn draw_code(&self) -> List {
-        if let Some(selected_file) = self.selected_file {
-            if selected_file < self.found_files.len() {
-                if let Ok(contents) = std::fs::read_to_string(&self.found_files[selected_file]) {
+    fn draw_code(&self) -> List<'_> {
+        if let Some(selected_file) = self.browser.selected_file() {
+            if selected_file < self.browser.files().len() {
+                if let Ok(contents) = std::fs::read_to_string(&self.browser.files()[selected_file]) {
                     let lines: Vec<String> = contents.lines().map(|line| line.to_string()).collect();
+                    // lines of the file are counted from one, like in the simulation
                     let max_index_width = lines.len().to_string().len();
 
                     let items: Vec<ListItem> = lines.iter().enumerate()
                         .map(|(i, line)| {
                             let index_span = Span::styled(
-                                format!("{:width$}| ", i, width = max_index_width),
+                                format!("{:width$}| ", i + 1, width = max_index_width),
                                 Style::default().fg(Color::DarkGray),
                             );
 
-                            let line_span = Span::styled(
-                                line.clone(),
-                                Style::default(),
-                            );
-
-                            ListItem::new(Line::from(vec![index_span, line_span]))
+                            let mut spans = vec![index_span];
+                            spans.extend(highlight::highlight_line(line, &self.theme));
+                            ListItem::new(Line::from(spans))
                         })
                         .collect();
 
@@ -207,7 +295,11 @@ impl Menu {
             )
     }
     
-    fn draw_error(&self) -> Paragraph {
+    pub fn set_error(&mut self, error: String) {
+        self.error = Some(error);
+    }
+
+    fn draw_error(&self) -> Paragraph<'_> {
         Paragraph::new(self.error.clone().unwrap()).fg(Color::Red)
             .block(
                 Block::default()
@@ -218,96 +310,216 @@ impl Menu {
             )
     }
 
-    fn draw_input(&self) -> Paragraph {
+    fn draw_input(&self) -> Paragraph<'_> {
         let border_color = match self.state {
             MenuState::SelectingFile => Color::DarkGray,
-            MenuState::SpecifyingInput => Color::White,
+            MenuState::SpecifyingInput | MenuState::NamingPreset => Color::White,
+        };
+
+        let saved_input = self.saved_input_index.map(|i| match &self.saved_inputs[i].preset {
+            Some(name) => format!(" - preset {}", name),
+            None => format!(" - history {}/{}", i + 1, self.saved_inputs.len()),
+        });
+
+        let text = match self.state {
+            MenuState::NamingPreset => format!("Preset name: {}_", self.preset_name),
+            _ => format!("[{}]", self.input),
         };
 
-        Paragraph::new(format!("[{}]", self.input))
+        Paragraph::new(text)
             .block(Block::default()
                 .borders(Borders::ALL)
                 .fg(border_color)
-                .title("Input")
+                .title(format!("Input ({}, {}, {}){}", self.mode, self.io_mode, self.strict_mode, saved_input.unwrap_or_default()))
                 .padding(Padding::symmetric(2, 1))
             )
     }
 
+    pub fn help_section(&self) -> Section {
+        match self.state {
+            MenuState::SelectingFile => Section::Files,
+            MenuState::SpecifyingInput | MenuState::NamingPreset => Section::Input,
+        }
+    }
+
+    // free text is being typed, so printable keys are not shortcuts,
+    // the file filter only counts once it has been started
+    pub fn is_typing(&self) -> bool {
+        match self.state {
+            MenuState::SelectingFile => !self.browser.filter().is_empty(),
+            MenuState::SpecifyingInput => false,
+            MenuState::NamingPreset => true,
+        }
+    }
+
     pub fn handle_input(&mut self, key: KeyEvent) -> MenuHandleResult {
         match self.state {
             MenuState::SelectingFile => self.handle_selecting_file(key),
             MenuState::SpecifyingInput => self.handle_specifying_input(key),
+            MenuState::NamingPreset => self.handle_naming_preset(key),
+        }
+    }
+
+    pub fn handle_mouse(&mut self, mouse: MouseEvent) {
+        let area = self.file_list_area.get();
+        if self.state != MenuState::SelectingFile || !area.contains(Position::new(mouse.column, mouse.row)) {
+            return;
+        }
+
+        match mouse.kind {
+            MouseEventKind::ScrollUp => self.move_file_selection(-1),
+            MouseEventKind::ScrollDown => self.move_file_selection(1),
+            // the first click selects a row, clicking it again opens it like Enter
+            MouseEventKind::Down(MouseButton::Left) => {
+                // skip the border and padding
+                let Some(row) = mouse.row.checked_sub(area.y + 2) else {
+                    return;
+                };
+                let row = self.file_list_offset.get() + row as usize;
+                if Some(row) != self.browser.selected_row() {
+                    self.error = None;
+                    self.browser.select_row(row);
+                } else if self.browser.selected_file().is_none() {
+                    self.browser.toggle_collapsed();
+                } else {
+                    self.confirm_file_selection();
+                }
+            }
+            _ => {}
         }
     }
 
     fn handle_selecting_file(&mut self, key: KeyEvent) -> MenuHandleResult {
-        match key.code {
+        match self.keys.action(Section::Files, &key) {
             // move selection up
-            KeyCode::Up => {
-                self.move_file_selection(-1);
-                MenuHandleResult::Continue
-            }
+            Some(Action::SelectPrevious) => self.move_file_selection(-1),
             // move selection down
-            KeyCode::Down => {
-                self.move_file_selection(1);
-                MenuHandleResult::Continue
+            Some(Action::SelectNext) => self.move_file_selection(1),
+            // fold and unfold folders
+            Some(Action::FoldFolder) => self.browser.set_collapsed(true),
+            Some(Action::UnfoldFolder) => self.browser.set_collapsed(false),
+            // open the selected program in the editor
+            Some(Action::EditFile) => {
+                if let Some(program) = self.selected_program() {
+                    return MenuHandleResult::Edit(program);
+                }
+            }
+            // rescan the program directories
+            Some(Action::Rescan) => self.scan_for_files(),
+            // confirm file selection, or unfold the selected folder
+            Some(Action::OpenFile) if self.browser.selected_file().is_none() && self.browser.selected_row().is_some() => {
+                self.browser.toggle_collapsed();
             }
-            // confirm file selection
-            KeyCode::Enter => self.confirm_file_selection(),
-            // exit
-            KeyCode::Esc => MenuHandleResult::Exit,
-            // ignore other keys
-            _ => MenuHandleResult::Continue,
+            Some(Action::OpenFile) => return self.confirm_file_selection(),
+            // clear the filter first, then exit
+            Some(Action::Exit) if !self.browser.filter().is_empty() => self.browser.clear_filter(),
+            Some(Action::Exit) => return MenuHandleResult::Exit,
+            // narrow down the file list
+            _ => match key.code {
+                KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    self.error = None;
+                    self.browser.push_filter(c);
+                }
+                KeyCode::Backspace => {
+                    self.error = None;
+                    self.browser.pop_filter();
+                }
+                // ignore other keys
+                _ => {}
+            },
         }
+        MenuHandleResult::Continue
     }
 
     fn handle_specifying_input(&mut self, key: KeyEvent) -> MenuHandleResult {
-        match key.code {
-            // add character to input
-            KeyCode::Char(c) => {
-                self.input_char(c);
-                MenuHandleResult::Continue
+        match self.keys.action(Section::Input, &key) {
+            // start naming a preset for the current input
+            Some(Action::SavePreset) => {
+                self.preset_name.clear();
+                self.state = MenuState::NamingPreset;
             }
-            // remove last character from input
-            KeyCode::Backspace => {
-                self.delete_char();
-                MenuHandleResult::Continue
+            // browse presets and previous inputs
+            Some(Action::PreviousInput) => self.move_saved_input_selection(1),
+            Some(Action::NextInput) => self.move_saved_input_selection(-1),
+            // toggle between RAM and RASP execution
+            Some(Action::ToggleRasp) => {
+                self.mode = match self.mode {
+                    ExecutionMode::Ram => ExecutionMode::Rasp(self.rasp_base),
+                    ExecutionMode::Rasp(_) => ExecutionMode::Ram,
+                };
             }
+            // toggle between a fixed tape and prompting for values once it runs out
+            Some(Action::ToggleInteractive) => {
+                self.io_mode = match self.io_mode {
+                    IoMode::Tape => IoMode::Interactive,
+                    IoMode::Interactive => IoMode::Tape,
+                };
+            }
+            // lenient, then warnings about suspicious memory accesses, then errors
+            Some(Action::ToggleStrict) => self.strict_mode = self.strict_mode.next(),
             // confirm input
-            KeyCode::Enter => self.confirm_input(),
+            Some(Action::StartSimulation) => return self.confirm_input(),
             // return to file selection
-            KeyCode::Esc => {
-                self.state = MenuState::SelectingFile;
-                MenuHandleResult::Continue
+            Some(Action::BackToFiles) => self.state = MenuState::SelectingFile,
+            _ => match key.code {
+                // add character to input
+                KeyCode::Char(c) => {
+                    self.saved_input_index = None;
+                    self.input_char(c);
+                }
+                // remove last character from input
+                KeyCode::Backspace => {
+                    self.saved_input_index = None;
+                    self.delete_char();
+                }
+                // ignore other keys
+                _ => {}
+            },
+        }
+        MenuHandleResult::Continue
+    }
+
+    fn handle_naming_preset(&mut self, key: KeyEvent) -> MenuHandleResult {
+        // the keys that confirm and leave the input also save and cancel the name
+        match self.keys.action(Section::Input, &key) {
+            // save the preset and return to the input
+            Some(Action::StartSimulation) => {
+                let program = self.selected_program().unwrap_or_default();
+                match session::save_preset(&program, &self.preset_name, &self.input) {
+                    Ok(()) => {
+                        self.error = None;
+                        self.state = MenuState::SpecifyingInput;
+                        self.load_saved_inputs();
+                    }
+                    Err(err) => self.error = Some(err),
+                }
             }
-            // ignore other keys
-            _ => MenuHandleResult::Continue,
+            Some(Action::BackToFiles) => self.state = MenuState::SpecifyingInput,
+            _ => match key.code {
+                KeyCode::Char(c) => self.preset_name.push(c),
+                KeyCode::Backspace => {
+                    self.preset_name.pop();
+                }
+                _ => {}
+            },
         }
+        MenuHandleResult::Continue
     }
 
     fn move_file_selection(&mut self, direction: i32) {
         self.error = None;
-        if let Some(selected) = self.selected_file {
-            let new_selection = selected as i32 + direction;
-            if new_selection >= 0 && new_selection < self.found_files.len() as i32 {
-                self.selected_file = Some(new_selection as usize);
-            }
-        } else {
-            self.selected_file = Some(0);
-        }
+        self.browser.move_selection(direction);
     }
 
     fn confirm_file_selection(&mut self) -> MenuHandleResult {
-        if let Some(selected) = self.selected_file {
-            if selected >= self.found_files.len() {
-                self.error = Some("No file selected".to_string());
-                return MenuHandleResult::Continue;
-            }
-            match self.parse_machine(&self.found_files[selected]) {
+        if let Some(selected) = self.selected_program() {
+            match self.parse_machine(&selected) {
                 Ok(machine) => {
                     self.error = None;
                     self.state = MenuState::SpecifyingInput;
                     self.selected_machine = Some(machine);
+                    self.load_saved_inputs();
+                    self.load_input_directive();
                     MenuHandleResult::Continue
                 }
                 Err(err) => {
@@ -321,6 +533,67 @@ impl Menu {
         }
     }
 
+    fn selected_program(&self) -> Option<String> {
+        self.browser.selected_file()
+            .and_then(|selected| self.browser.files().get(selected))
+            .cloned()
+    }
+
+    fn load_saved_inputs(&mut self) {
+        let Some(program) = self.selected_program() else {
+            return;
+        };
+
+        let presets = session::load_presets(&program).into_iter()
+            .map(|(name, tape)| SavedInput { preset: Some(name), tape });
+        let recent = self.history.get(&program).iter()
+            .map(|tape| SavedInput { preset: None, tape: tape.clone() });
+
+        self.saved_inputs = presets.chain(recent).collect();
+        self.saved_input_index = None;
+    }
+
+    fn load_input_directive(&mut self) {
+        // prefill the input from the `# input:` header of the program
+        let Some(program) = self.selected_program() else {
+            return;
+        };
+        let Ok(source) = std::fs::read_to_string(&program) else {
+            return;
+        };
+
+        match Parser::parse_input_directive(&source) {
+            Some(Ok(tape)) => {
+                self.input = tape.iter()
+                    .map(|value| value.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ");
+            }
+            Some(Err(err)) => self.error = Some(format!("Input header: {}", err)),
+            None => {}
+        }
+    }
+
+    fn move_saved_input_selection(&mut self, direction: i32) {
+        // index None is the input typed by the user, kept aside while browsing
+        let new_index = match self.saved_input_index {
+            None if direction > 0 && !self.saved_inputs.is_empty() => {
+                self.draft_input = self.input.clone();
+                Some(0)
+            }
+            Some(0) if direction < 0 => None,
+            Some(i) => Some((i as i32 + direction).clamp(0, self.saved_inputs.len() as i32 - 1) as usize),
+            None => None,
+        };
+
+        self.input = match new_index {
+            Some(i) => self.saved_inputs[i].tape.clone(),
+            None if self.saved_input_index.is_some() => self.draft_input.clone(),
+            None => self.input.clone(),
+        };
+        self.saved_input_index = new_index;
+    }
+
     fn input_char(&mut self, c: char) {
         // if char is a digit, add it to the input
         // if char is a comma or space, add ", " to the input
@@ -345,12 +618,10 @@ impl Menu {
                 }
                 self.input.push_str(", ");
             }
-            '-' => {
-                // if the last character is a comma or space, add it
-                // otherwise, ignore
-                if self.input.is_empty() || self.input.ends_with(", ") {
-                    self.input.push(c);
-                }
+            // if the last character is a comma or space, add it
+            // otherwise, ignore
+            '-' if self.input.is_empty() || self.input.ends_with(", ") => {
+                self.input.push(c);
             }
             _ => {}
         }
@@ -371,15 +642,27 @@ impl Menu {
             Ok(input_tape) => {
                 self.error = None;
                 self.state = MenuState::SelectingFile;
+                // failing to remember the input should not prevent running it, an empty one is not remembered
+                let tape = self.input.trim();
+                if let Some(program) = self.selected_program().filter(|_| !tape.is_empty()) {
+                    let _ = self.history.record(&program, tape);
+                }
                 if let Some(machine) = self.selected_machine.take() {
-                    let filename = self.found_files[self.selected_file.unwrap()]
-                        .clone()
-                        .split('/')
-                        .last()
-                        .unwrap()
-                        .to_string()
-                        .replace(".ram", "");
-                    MenuHandleResult::Machine(filename, machine.with_input(input_tape))
+                    let program = self.selected_program().unwrap_or_default();
+                    let mut machine = machine.with_input(input_tape)
+                        .with_io_mode(self.io_mode)
+                        .with_strict_mode(self.strict_mode)
+                        .with_profiling();
+                    if let Some(limit) = self.memory_limit {
+                        machine = machine.with_memory_limit(limit);
+                    }
+                    match machine.with_mode(self.mode) {
+                        Ok(machine) => MenuHandleResult::Machine(program, Box::new(machine)),
+                        Err(err) => {
+                            self.error = Some(err);
+                            MenuHandleResult::Continue
+                        }
+                    }
                 } else {
                     self.error = Some("The machine somehow escaped".to_string());
                     MenuHandleResult::Continue
@@ -393,89 +676,31 @@ impl Menu {
     }
 
     fn scan_for_files(&mut self) {
-        let executable_path_result = std::env::current_exe();
-        let executable_path = match executable_path_result {
-            Ok(path) => path,
-            Err(error) => {
-                eprintln!("Failed to get current executable path: {}", error);
-                return;
-            }
-        };
+        if !self.roots.is_empty() {
+            self.browser.set_files(discovery::scan(&self.roots, &self.ignore));
+            return;
+        }
 
-        let executable_directory_option = executable_path.parent();
-        let executable_directory = match executable_directory_option {
-            Some(directory) => directory.to_path_buf(),
-            None => {
-                eprintln!("Executable has no parent directory.");
-                return;
-            }
-        };
+        // without configured directories, look next to the executable and then in the working directory
+        let executable_directory = std::env::current_exe()
+            .ok()
+            .and_then(|path| path.parent().map(|directory| directory.display().to_string()));
 
-        self.found_files = walkdir::WalkDir::new(executable_directory)
-            .into_iter()
-            .filter_map(|entry_result| entry_result.ok())
-            .filter(|entry| {
-                let extension = entry.path().extension();
-                match extension {
-                    Some(ext) => ext == "ram",
-                    None => false,
-                }
-            })
-            .map(|entry| {
-                // normalize Windows backslashes to forward slashes for display
-                let path_as_string = entry.path().display().to_string().replace("\\", "/");
-                path_as_string
-            })
-            .collect();
-
-        if self.found_files.is_empty() {
-            self.found_files.extend(
-                walkdir::WalkDir::new(".")
-                    .into_iter()
-                    .filter_map(|entry_result| entry_result.ok())
-                    .filter(|entry| {
-                        let extension = entry.path().extension();
-                        match extension {
-                            Some(ext) => ext == "ram",
-                            None => false,
-                        }
-                    })
-                        .map(|entry| {
-                            // normalize Windows backslashes to forward slashes for display
-                            let path_as_string = entry.path().display().to_string().replace("\\", "/");
-                            path_as_string
-                        })
-            );
+        let mut files = match executable_directory {
+            Some(directory) => discovery::scan(&[directory], &self.ignore),
+            None => Vec::new(),
+        };
+        if files.is_empty() {
+            files = discovery::scan(&[".".to_string()], &self.ignore);
         }
+        self.browser.set_files(files);
     }
 
     fn parse_input(&self) -> Result<Vec<i32>, String> {
-        if self.input.is_empty() {
-            return Ok(Vec::new());
-        }
-
-        if self.input.trim().ends_with(',') {
-            return Err("Input cannot end with a comma.".to_string());
-        }
-
-        self.input.split(',')
-            .map(|s| {
-                let trimmed = s.trim();
-                trimmed.parse::<i32>().map_err(|_| {
-                    if trimmed.parse::<i64>().is_ok() {
-                        format!(
-                            "Input value {trimmed} is too {}.", 
-                            if trimmed.starts_with('-') { "small" } else { "large" }
-                        )
-                    } else {
-                        "Invalid input.".to_string()
-                    }
-                })
-            })
-            .collect()
+        Parser::parse_tape(&self.input)
     }
 
     fn parse_machine(&self, filename: &str) -> Result<RamMachine, String> {
-        Parser::parse_file(filename)
+        Parser::new().with_dialect(self.dialect).parse_file(filename)
     }
 }
\ No newline at end of file
@@ -1,3 +1,16 @@
 pub mod handler;
 mod simulation;
-mod menu;
\ No newline at end of file
+mod menu;
+pub mod session;
+mod storage;
+mod config;
+pub mod discovery;
+pub mod file_browser;
+mod editor;
+mod highlight;
+mod theme;
+pub mod keys;
+mod help;
+mod terminal;
+mod crash;
+mod flowchart;
\ No newline at end of file
@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::ui::storage;
+
+const HISTORY_FILE: &str = "history";
+const HISTORY_LENGTH: usize = 5;
+const PRESET_EXTENSION: &str = "inputs";
+
+// last input tapes per program, stored as `program<TAB>tape` lines
+pub struct InputHistory {
+    path: PathBuf,
+    entries: HashMap<String, Vec<String>>,
+}
+
+impl InputHistory {
+    pub fn load() -> InputHistory {
+        InputHistory::load_from(storage::app_dir().join(HISTORY_FILE))
+    }
+
+    pub fn load_from(path: PathBuf) -> InputHistory {
+        let mut entries: HashMap<String, Vec<String>> = HashMap::new();
+
+        if let Ok(contents) = fs::read_to_string(&path) {
+            for line in contents.lines() {
+                if let Some((program, tape)) = line.split_once('\t') {
+                    entries.entry(program.to_string()).or_default().push(tape.to_string());
+                }
+            }
+        }
+
+        InputHistory { path, entries }
+    }
+
+    // most recent first
+    pub fn get(&self, program: &str) -> &[String] {
+        self.entries.get(program).map_or(&[], |tapes| tapes.as_slice())
+    }
+
+    pub fn record(&mut self, program: &str, tape: &str) -> Result<(), String> {
+        let tapes = self.entries.entry(program.to_string()).or_default();
+        tapes.retain(|existing| existing != tape);
+        tapes.insert(0, tape.to_string());
+        tapes.truncate(HISTORY_LENGTH);
+        self.save()
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let mut programs: Vec<&String> = self.entries.keys().collect();
+        programs.sort();
+
+        let contents: String = programs.into_iter()
+            .flat_map(|program| self.entries[program].iter()
+                .map(move |tape| format!("{}\t{}\n", program, tape)))
+            .collect();
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        fs::write(&self.path, contents).map_err(|e| e.to_string())
+    }
+}
+
+// named input tapes stored next to the program, `program.ram` -> `program.inputs`
+pub fn preset_path(program: &str) -> PathBuf {
+    Path::new(program).with_extension(PRESET_EXTENSION)
+}
+
+pub fn load_presets(program: &str) -> Vec<(String, String)> {
+    let Ok(contents) = fs::read_to_string(preset_path(program)) else {
+        return Vec::new();
+    };
+
+    contents.lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(name, tape)| (name.trim().to_string(), tape.trim().to_string()))
+        .collect()
+}
+
+pub fn save_preset(program: &str, name: &str, tape: &str) -> Result<(), String> {
+    let name = name.trim();
+    if name.is_empty() || name.contains('=') {
+        return Err("Preset name cannot be empty or contain '='.".to_string());
+    }
+
+    // replace a preset of the same name, keep the rest in order
+    let mut presets = load_presets(program);
+    presets.retain(|(existing, _)| existing != name);
+    presets.push((name.to_string(), tape.to_string()));
+
+    let contents: String = presets.iter()
+        .map(|(name, tape)| format!("{} = {}\n", name, tape))
+        .collect();
+    fs::write(preset_path(program), contents).map_err(|e| e.to_string())
+}
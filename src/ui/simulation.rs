@@ -1,44 +1,116 @@
+use crate::cfg::ControlFlowGraph;
+use crate::parser::Parser;
+use crate::ram::execution_mode::ExecutionMode;
+use crate::ram::instruction::Instruction;
 use crate::ram::instruction_op::InstructionOp::Empty;
 use crate::ram::machine::RamMachine;
-use ratatui::crossterm::event::{KeyCode, KeyEvent};
-use ratatui::layout::{Constraint, Direction, Layout};
+use crate::ram::rasp;
+use crate::ram::run::{RunOptions, RunOutcome};
+use crate::ram::types::Number;
+use crate::ram::watch::{Address, Watch};
+use std::cell::Cell;
+use crate::ui::crash::CrashGuard;
+use std::collections::HashSet;
+use std::ops::Range;
+use crate::ram::types::Index;
+use crate::ui::{flowchart, highlight};
+use crate::ui::config::Config;
+use crate::ui::keys::{Action, KeyBindings, Section};
+use crate::ui::theme::Theme;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::{Constraint, Direction, Layout, Position, Rect};
 use ratatui::prelude::{Color, Line, Modifier, Span, Style, Text};
-use ratatui::widgets::{Block, Borders, List, ListItem, Padding};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Padding};
 use ratatui::Frame;
 use ratatui::style::Stylize;
 
 pub enum SimulationHandleResult {
     Continue,
+    Edit(String),
     Exit,
 }
 
 #[derive(PartialEq)]
 pub enum SimulationState {
     Running,
+    AwaitingInput { value: String, error: Option<String> },
     Finished,
     Error(String),
 }
 
 pub struct Simulation {
+    path: String,
     name: String,
     machine: RamMachine,
     label_indent: usize,
     state: SimulationState,
+    theme: Theme,
+    keys: KeyBindings,
+    memory_scroll: usize,
+    // manual scroll of the code pane, None follows the instruction pointer
+    code_scroll: Option<usize>,
+    code_height: Cell<usize>,
+    watches: Vec<Watch>,
+    prompt: Option<Prompt>,
+    breakpoints: HashSet<Index>,
+    // lowered expressions are shown as their instructions instead of the source line
+    expanded: bool,
+    // the code pane shows the basic blocks of the program instead of its lines
+    flowchart: Option<ControlFlowGraph>,
+    message: Option<String>,
+    // where the panes were drawn, for mouse clicks and scrolling
+    code_area: Cell<Rect>,
+    memory_area: Cell<Rect>,
+}
+
+// steps a single continue may take before handing control back to the user
+const CONTINUE_LIMIT: Index = 100_000;
+
+#[derive(PartialEq)]
+enum PromptKind {
+    Watch,
+    Label,
+}
+
+// a line of text being typed, with the error of the last attempt
+struct Prompt {
+    kind: PromptKind,
+    input: String,
+    error: Option<String>,
 }
 
 impl Simulation {
-    pub fn new(name: String, machine: RamMachine) -> Self {
+    pub fn new(path: String, machine: RamMachine, config: &Config) -> Self {
+        let name = path
+            .split('/')
+            .next_back()
+            .unwrap_or_default()
+            .replace(".ram", "");
+
         let label_indent = machine.get_program().iter()
-            .filter_map(|instruction| instruction.label.as_ref())
-            .map(|label| label.len())
+            .map(|instruction| Self::label_text(instruction).chars().count())
             .max()
             .unwrap_or(0);
         
         Simulation {
+            path,
             name,
             machine,
             label_indent,
-            state: SimulationState::Running
+            state: SimulationState::Running,
+            theme: config.theme,
+            keys: config.keys.clone(),
+            memory_scroll: 0,
+            code_scroll: None,
+            code_height: Cell::new(0),
+            watches: Vec::new(),
+            prompt: None,
+            breakpoints: HashSet::new(),
+            expanded: false,
+            flowchart: None,
+            message: None,
+            code_area: Cell::new(Rect::default()),
+            memory_area: Cell::new(Rect::default()),
         }
     }
 
@@ -54,11 +126,12 @@ impl Simulation {
             )
             .areas(f.area());
 
-        let [code_area, bottom] = Layout::default()
+        let [code_area, watches_area, bottom] = Layout::default()
             .direction(Direction::Vertical)
             .constraints(
                 [
                     Constraint::Percentage(100), // Code
+                    Constraint::Length(self.watches_height()), // Watches
                     Constraint::Min(12), // Bottom
                 ].as_ref(),
             )
@@ -85,16 +158,28 @@ impl Simulation {
             .areas(tapes);
 
         // Draw widgets
-        f.render_widget(self.draw_code(), code_area);
-        f.render_widget(self.draw_memory(), memory_area);
+        // rows left inside the borders and padding
+        self.code_height.set(code_area.height.saturating_sub(4) as usize);
+        self.code_area.set(code_area);
+        self.memory_area.set(memory_area);
+        let mut code_state = ListState::default().with_offset(self.code_offset());
+        f.render_stateful_widget(self.draw_code(), code_area, &mut code_state);
+        let mut memory_state = ListState::default().with_offset(self.memory_offset());
+        f.render_stateful_widget(self.draw_memory(), memory_area, &mut memory_state);
+        if watches_area.height > 0 {
+            f.render_widget(self.draw_watches(), watches_area);
+        }
         f.render_widget(self.draw_input_tape(), input_tape_area);
         f.render_widget(self.draw_output_tape(), output_tape_area);
         f.render_widget(self.draw_info(), info_area);
     }
 
-    fn draw_info(&self) -> List {
+    fn draw_info(&self) -> List<'_> {
         let mut info_widgets: Vec<ListItem> = vec![
-            ListItem::new(Text::from(format!("Running simulation: {}", self.name))),
+            ListItem::new(Text::from(match self.machine.get_mode() {
+                ExecutionMode::Ram => format!("Running simulation: {}", self.name),
+                mode => format!("Running simulation: {} ({})", self.name, mode),
+            })),
             ListItem::new(Text::from(format!("│ Instruction Pointer: {}", self.machine.get_instruction_pointer()))),
             ListItem::new(Text::from(format!("│ Input Pointer: {}", self.machine.get_input_pointer()))),
             ListItem::new(Text::from(format!("│ Output Tape Length: {}", self.machine.get_output().len()))),
@@ -102,6 +187,23 @@ impl Simulation {
             ListItem::new(Text::raw("")),
         ];
 
+        if let Some(message) = &self.message {
+            info_widgets.push(ListItem::new(Text::styled(message.clone(), Style::default().fg(Color::Cyan))));
+        }
+
+        // strict warnings pile up while running, the newest one is the most relevant
+        let warnings = self.machine.get_warnings();
+        if let Some(last) = warnings.last() {
+            info_widgets.push(ListItem::new(Text::styled(
+                format!("Warnings: {}", warnings.len()),
+                Style::default().fg(Color::Yellow),
+            )));
+            info_widgets.push(ListItem::new(Text::styled(
+                format!("│ {}", last),
+                Style::default().fg(Color::Yellow),
+            )));
+        }
+
         match &self.state {
             SimulationState::Error(message) => {
                 info_widgets.push(ListItem::new(Text::styled(
@@ -113,17 +215,31 @@ impl Simulation {
                     Style::default().fg(Color::Red),
                 )));
                 info_widgets.push(ListItem::new(Text::styled(
-                    "│ Press 'Esc' to exit",
+                    self.exit_hint(),
                     Style::default().fg(Color::Red),
                 )));
             }
+            SimulationState::AwaitingInput { error, .. } => {
+                info_widgets.push(ListItem::new(Text::styled(
+                    "Waiting for input!",
+                    Style::default().fg(Color::Yellow),
+                )));
+                info_widgets.push(ListItem::new(Text::styled(
+                    format!("│ {}", error.as_deref().unwrap_or("Type a value and press 'Enter'")),
+                    Style::default().fg(if error.is_some() { Color::Red } else { Color::Yellow }),
+                )));
+                info_widgets.push(ListItem::new(Text::styled(
+                    "│ Press 'Esc' to cancel",
+                    Style::default().fg(Color::Yellow),
+                )));
+            }
             SimulationState::Finished => {
                 info_widgets.push(ListItem::new(Text::styled(
                     "Simulation Finished!",
                     Style::default().fg(Color::Green),
                 )));
                 info_widgets.push(ListItem::new(Text::styled(
-                    "│ Press 'Esc' to exit",
+                    self.exit_hint(),
                     Style::default().fg(Color::Green),
                 )));
             }
@@ -138,7 +254,15 @@ impl Simulation {
             )
     }
 
-    fn draw_output_tape(&self) -> List {
+    fn exit_hint(&self) -> String {
+        format!(
+            "│ Press '{}' to edit, '{}' to exit",
+            self.keys.key_of(Action::EditProgram),
+            self.keys.key_of(Action::StopSimulation),
+        )
+    }
+
+    fn draw_output_tape(&self) -> List<'_> {
         let mut pointer_line_spans = vec![Span::raw(" ")];
         let mut number_line_spans = vec![Span::raw("[")];
 
@@ -174,7 +298,7 @@ impl Simulation {
             )
     }
 
-    fn draw_input_tape(&self) -> List {
+    fn draw_input_tape(&self) -> List<'_> {
         let input_pointer = self.machine.get_input_pointer();
         let input_values = self.machine.get_input();
 
@@ -218,13 +342,19 @@ impl Simulation {
                 ),
             ]);
 
+            // an inline prompt replaces the placeholder while waiting for input
+            let placeholder = match &self.state {
+                SimulationState::AwaitingInput { value, .. } => format!("? {}_", value),
+                _ => "_".to_string(),
+            };
+
             number_line_spans.extend([
                 Span::styled(
                     if input_pointer == 0 { "" } else { ", " },
                     Style::default().fg(Color::DarkGray)
                 ),
                 Span::styled(
-                    "_", 
+                    placeholder,
                     Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
                 ),
             ]);
@@ -249,21 +379,38 @@ impl Simulation {
         )
     }
 
-    fn draw_memory(&self) -> List {
+    fn draw_memory(&self) -> List<'_> {
         let mut memory_items: Vec<(&i32, &i32)> = self.machine.get_memory().iter().collect();
         memory_items.sort_by_key(|&(i, _)| i);
-        let max_index_width = memory_items.last().map_or(0, |(i, _)| i.to_string().len());
+        let max_index_width = memory_items.iter().map(|(i, _)| i.to_string().len()).max().unwrap_or(0);
+
+        // in RASP mode, highlight the cells holding the currently executing instruction
+        let executing = match self.machine.get_mode() {
+            ExecutionMode::Rasp(base) if self.is_running() => {
+                let address = rasp::address_of(base, self.machine.get_instruction_pointer());
+                address..address + rasp::INSTRUCTION_WIDTH
+            }
+            _ => 0..0,
+        };
 
         let memory_list_items: Vec<ListItem> = memory_items
             .iter()
             .map(|(i, &value)| {
+                let (index_style, value_style) = if executing.contains(i) {
+                    let style = Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD);
+                    (style, style)
+                } else if self.machine.get_changed().contains(i) {
+                    (Style::default().fg(Color::Green), Self::changed_style())
+                } else {
+                    (Style::default().fg(Color::DarkGray), Style::default())
+                };
                 let index_span = Span::styled(
                     format!("{:width$}| ", i, width = max_index_width),
-                    Style::default().fg(Color::DarkGray)
+                    index_style
                 );
                 let value_span = Span::styled(
                     format!("{}", value),
-                    Style::default()
+                    value_style
                 );
                 ListItem::new(Line::from(vec![index_span, value_span]))
             })
@@ -281,58 +428,85 @@ impl Simulation {
             )
     }
 
-    fn draw_code(&self) -> List {
-        let max_index_width = self.machine.get_program().len().to_string().len();
-        let code_items: Vec<ListItem> = self
-            .machine.get_program()
-            .iter()
-            .enumerate()
-            .map(|(i, line)| {
+    fn draw_code(&self) -> List<'_> {
+        if let Some(cfg) = &self.flowchart {
+            return self.draw_flowchart(cfg);
+        }
+        let program = self.machine.get_program();
+        let shared = self.machine.get_shared_program();
+        let pointer = self.machine.get_instruction_pointer();
+        // the gutter shows the lines of the file, counted from one
+        let max_index_width = program.len().checked_sub(1).map_or(1, |last| shared.line(last) + 1).to_string().len();
+        let max_executions = self.machine.get_profile().map_or(0, |profile| profile.max_executions());
+        let heat_width = max_executions.to_string().len();
+        let code_items: Vec<ListItem> = self.code_rows()
+            .into_iter()
+            .map(|row| {
+                let i = row.start;
+                let line = &program[i];
+                let current = row.contains(&pointer);
                 let index_span = Span::styled(
-                    format!("{:width$}| ", i, width = max_index_width),
-                    if i == self.machine.get_instruction_pointer() {
+                    format!("{:width$}", shared.line(i) + 1, width = max_index_width),
+                    if current {
                         Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
                     } else {
                         Style::default().fg(Color::DarkGray)
                     }
                 );
+                let breakpoint_span = if self.breakpoints.contains(&i) {
+                    Span::styled("● ", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))
+                } else {
+                    Span::styled("| ", Style::default().fg(Color::DarkGray))
+                };
 
-                let label_span = if let Some(label) = &line.label {
-                    let label_str = format!("{}:", label);
-                    Span::from(format!("{:width$} ", label_str, width = self.label_indent + 1))
+                // how often the line ran, colored by how hot it is compared to the hottest one
+                let executions = self.machine.get_profile().map_or(0, |profile| profile.executions(i));
+                let heat_span = if executions == 0 {
+                    Span::raw(" ".repeat(heat_width + 1))
                 } else {
-                    Span::raw(" ".repeat(self.label_indent + 2))
+                    Span::styled(
+                        format!("{:>width$} ", executions, width = heat_width),
+                        Self::heat_style(executions as f64 / max_executions as f64),
+                    )
                 };
 
-                let line_span = Span::styled(
-                    format!("{}", line.op),
-                    if i == self.machine.get_instruction_pointer() {
-                        Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
-                    } else {
-                        Style::default()
-                    }
+                let label_span = Span::styled(
+                    format!("{:width$} ", Self::label_text(line), width = self.label_indent),
+                    self.theme.label,
                 );
 
+                let op = match (&line.source, self.machine.get_mode()) {
+                    (Some(source), _) if row.len() > 1 => Ok(source.clone()),
+                    // the code in memory is what runs, the program may have rewritten it
+                    (_, ExecutionMode::Rasp(_)) => self.machine.instruction_at(i).map(|op| format!("{}", op)),
+                    _ => Ok(format!("{}", line.op)),
+                };
+                // the current instruction stays plain yellow so it stands out from the highlighting
+                let mut line_spans = match op {
+                    Err(err) => vec![Span::styled(err, self.theme.invalid)],
+                    Ok(op) if current => vec![Span::styled(op, Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))],
+                    Ok(op) => highlight::highlight_line(&op, &self.theme),
+                };
+                // how far into a lowered line the machine is
+                if current && row.len() > 1 {
+                    line_spans.push(Span::styled(
+                        format!(" [{}/{}]", pointer - row.start + 1, row.len()),
+                        Style::default().fg(Color::DarkGray),
+                    ));
+                }
+
                 let comment_span = line.comment.as_ref().map_or_else(
                     || Span::raw(""),
                     |comment| {
                         let prefix = if line.op == Empty { "# " } else { " # " };
-                        Span::styled(
-                            format!("{prefix}{comment}"),
-                            Style::default()
-                                .fg(Color::DarkGray)
-                        )
+                        Span::styled(format!("{prefix}{comment}"), self.theme.comment)
                     },
                 );
 
-                ListItem::new(
-                    Line::from(vec![
-                        index_span,
-                        label_span,
-                        line_span,
-                        comment_span
-                    ])
-                )
+                let mut spans = vec![index_span, breakpoint_span, heat_span, label_span];
+                spans.extend(line_spans);
+                spans.push(comment_span);
+                ListItem::new(Line::from(spans))
             })
             .collect();
 
@@ -343,32 +517,441 @@ impl Simulation {
                     SimulationState::Error(_) | SimulationState::Finished => Color::DarkGray,
                     _ => Color::Reset,
                 })
-                .title("Code")
+                .title(self.code_title())
+                .title_bottom(self.prompt_of(PromptKind::Label)
+                    .map(|prompt| Self::draw_prompt("Go to label", prompt))
+                    .unwrap_or_default())
                 .padding(Padding::symmetric(1, 1))
             )
     }
 
+    fn draw_flowchart(&self, cfg: &ControlFlowGraph) -> List<'_> {
+        let pointer = self.machine.get_instruction_pointer();
+        let items: Vec<ListItem> = flowchart::lines(cfg, pointer, &self.theme)
+            .into_iter()
+            .map(ListItem::new)
+            .collect();
+
+        List::new(items)
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .fg(match self.state {
+                    SimulationState::Error(_) | SimulationState::Finished => Color::DarkGray,
+                    _ => Color::Reset,
+                })
+                .title(self.code_title())
+                .title_bottom(self.prompt_of(PromptKind::Label)
+                    .map(|prompt| Self::draw_prompt("Go to label", prompt))
+                    .unwrap_or_default())
+                .padding(Padding::symmetric(1, 1))
+            )
+    }
+
+    fn code_title(&self) -> String {
+        let mut title = "Code".to_string();
+        if self.flowchart.is_some() {
+            title = format!("Flowchart ('{}' for code)", self.keys.key_of(Action::ToggleFlowchart));
+        } else if self.machine.get_program().iter().any(|instruction| instruction.source.is_some()) {
+            let key = self.keys.key_of(Action::ToggleExpansion);
+            title += &if self.expanded {
+                format!(" (expanded, '{}' for source)", key)
+            } else {
+                format!(" (source, '{}' to expand)", key)
+            };
+        }
+        if self.code_scroll.is_some() {
+            title += &format!(" (scrolled, '{}' to follow)", self.keys.key_of(Action::FollowPointer));
+        }
+        title
+    }
+
+    // instructions on every row of the code pane, a lowered line takes a single row unless expanded
+    fn code_rows(&self) -> Vec<Range<Index>> {
+        let program = self.machine.get_shared_program();
+        let mut rows: Vec<Range<Index>> = Vec::new();
+        for i in 0..program.len() {
+            match rows.last_mut() {
+                Some(row) if !self.expanded && program.line(row.start) == program.line(i) => row.end = i + 1,
+                _ => rows.push(i..i + 1),
+            }
+        }
+        rows
+    }
+
+    // row of the code pane holding the instruction, one past the last row for the end of the program
+    fn code_row_of(&self, index: Index) -> usize {
+        if let Some(cfg) = &self.flowchart {
+            return flowchart::row_of(cfg, index);
+        }
+        let rows = self.code_rows();
+        rows.iter().position(|row| row.contains(&index)).unwrap_or(rows.len())
+    }
+
+    // rows of the code pane in whichever view it shows
+    fn code_row_count(&self) -> usize {
+        match &self.flowchart {
+            Some(cfg) => flowchart::height(cfg),
+            None => self.code_rows().len(),
+        }
+    }
+
+    // all labels of the instruction, as in `start: loop:`
+    fn label_text(instruction: &Instruction) -> String {
+        instruction.labels.iter()
+            .map(|label| format!("{}:", label))
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    fn heat_style(ratio: f64) -> Style {
+        let color = match ratio {
+            r if r > 0.75 => Color::Red,
+            r if r > 0.5 => Color::LightRed,
+            r if r > 0.25 => Color::Yellow,
+            _ => Color::DarkGray,
+        };
+        Style::default().fg(color)
+    }
+
+    fn changed_style() -> Style {
+        Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)
+    }
+
+    // the memory list is scrolled by its first visible row
+    fn memory_offset(&self) -> usize {
+        self.memory_scroll.min(self.machine.get_memory().len().saturating_sub(1))
+    }
+
+    fn watches_height(&self) -> u16 {
+        let rows: usize = self.watches.iter()
+            .map(|watch| match watch {
+                Watch::Cell(_) => 1,
+                Watch::Range(..) => 2,
+            })
+            .sum::<usize>()
+            + usize::from(self.prompt_of(PromptKind::Watch).is_some());
+
+        if rows == 0 { 0 } else { rows as u16 + 2 }
+    }
+
+    fn draw_watches(&self) -> List<'_> {
+        let name_width = self.watches.iter()
+            .map(|watch| watch.to_string().len())
+            .max()
+            .unwrap_or(0);
+        let dim = Style::default().fg(Color::DarkGray);
+
+        let mut items = Vec::new();
+        for watch in &self.watches {
+            let name = Span::raw(format!("{:width$}  ", watch.to_string(), width = name_width));
+            match watch {
+                Watch::Cell(address) => {
+                    let cell = address.resolve(&self.machine);
+                    let mut spans = vec![name, self.cell_span(cell, 0)];
+                    if let Address::Indirect(_) = address {
+                        spans.push(Span::styled(format!("  @{}", cell), dim));
+                    }
+                    items.push(ListItem::new(Line::from(spans)));
+                }
+                // ranges are drawn as a row of cells like the tapes, with the addresses below
+                Watch::Range(..) => {
+                    let mut value_spans = vec![name, Span::raw("[")];
+                    let mut index_spans = vec![Span::raw(" ".repeat(name_width + 3))];
+                    let addresses = watch.addresses(&self.machine);
+                    for (i, &cell) in addresses.iter().enumerate() {
+                        let width = self.machine.get(cell).to_string().len().max(cell.to_string().len());
+                        let separator = if i + 1 == addresses.len() { "" } else { ", " };
+                        value_spans.push(self.cell_span(cell, width));
+                        value_spans.push(Span::raw(separator));
+                        index_spans.push(Span::styled(format!("{:>width$}{}", cell, " ".repeat(separator.len())), dim));
+                    }
+                    value_spans.push(Span::raw("]"));
+                    items.push(ListItem::new(Line::from(value_spans)));
+                    items.push(ListItem::new(Line::from(index_spans)));
+                }
+            }
+        }
+
+        if let Some(prompt) = self.prompt_of(PromptKind::Watch) {
+            items.push(ListItem::new(Self::draw_prompt("Add watch", prompt)));
+        }
+
+        List::new(items)
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title("Watches")
+                .padding(Padding::horizontal(2))
+            )
+    }
+
+    fn draw_prompt(title: &str, prompt: &Prompt) -> Line<'static> {
+        let mut spans = vec![Span::styled(
+            format!("{}: {}_", title, prompt.input),
+            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+        )];
+        if let Some(error) = &prompt.error {
+            spans.push(Span::styled(format!("  {}", error), Style::default().fg(Color::Red)));
+        }
+        Line::from(spans)
+    }
+
+    fn prompt_of(&self, kind: PromptKind) -> Option<&Prompt> {
+        self.prompt.as_ref().filter(|prompt| prompt.kind == kind)
+    }
+
+    // first visible line of the code pane, centered on the instruction pointer unless scrolled by hand
+    fn code_offset(&self) -> usize {
+        let height = self.code_height.get();
+        let max_offset = self.code_row_count().saturating_sub(height);
+        self.code_scroll
+            .unwrap_or_else(|| self.code_row_of(self.machine.get_instruction_pointer()).saturating_sub(height / 2))
+            .min(max_offset)
+    }
+
+    fn scroll_code(&mut self, lines: isize) {
+        let current = self.code_offset();
+        let max_offset = self.code_row_count().saturating_sub(self.code_height.get());
+        let offset = current.saturating_add_signed(lines).min(max_offset);
+        if offset != current {
+            self.code_scroll = Some(offset);
+        }
+    }
+
+    fn cell_span(&self, cell: Number, width: usize) -> Span<'static> {
+        let style = if self.machine.get_changed().contains(&cell) {
+            Self::changed_style()
+        } else {
+            Style::default()
+        };
+        Span::styled(format!("{:>width$}", self.machine.get(cell)), style)
+    }
+
+    fn is_running(&self) -> bool {
+        matches!(self.state, SimulationState::Running | SimulationState::AwaitingInput { .. })
+    }
+
     pub fn handle_input(&mut self, key: KeyEvent) -> SimulationHandleResult {
+        if self.prompt.is_some() {
+            return self.handle_prompt(key);
+        }
+        if let SimulationState::AwaitingInput { .. } = self.state {
+            return self.handle_awaiting_input(key);
+        }
+
+        match self.keys.action(Section::Simulation, &key) {
+            Some(Action::Step) if self.state == SimulationState::Running => self.step_line(),
+            // run until a breakpoint is hit
+            Some(Action::Continue) => self.run_until(None),
+            // edit the program once the simulation is over
+            Some(Action::EditProgram) if !self.is_running() => {
+                return SimulationHandleResult::Edit(self.path.clone());
+            }
+            Some(Action::ScrollMemoryUp) => self.memory_scroll = self.memory_offset().saturating_sub(1),
+            Some(Action::ScrollMemoryDown) => self.memory_scroll = self.memory_offset() + 1,
+            Some(Action::ScrollCodeUp) => self.scroll_code(-(self.code_height.get().max(1) as isize)),
+            Some(Action::ScrollCodeDown) => self.scroll_code(self.code_height.get().max(1) as isize),
+            Some(Action::FollowPointer) => self.code_scroll = None,
+            Some(Action::ToggleExpansion) => {
+                self.expanded = !self.expanded;
+                self.code_scroll = None;
+            }
+            // a RASP program can rewrite itself, so the graph of the parsed program would be wrong there
+            Some(Action::ToggleFlowchart) if matches!(self.machine.get_mode(), ExecutionMode::Rasp(_)) => {
+                self.message = Some("The flowchart is not available in RASP mode".to_string());
+            }
+            // a RAM program does not change while it runs, so the graph is built once per toggle
+            Some(Action::ToggleFlowchart) => {
+                self.flowchart = match self.flowchart {
+                    Some(_) => None,
+                    None => Some(ControlFlowGraph::build(self.machine.get_shared_program())),
+                };
+                self.code_scroll = None;
+            }
+            Some(Action::AddWatch) => {
+                self.prompt = Some(Prompt { kind: PromptKind::Watch, input: String::new(), error: None });
+            }
+            Some(Action::RemoveWatch) => {
+                self.watches.pop();
+            }
+            Some(Action::GoToLabel) => {
+                self.prompt = Some(Prompt { kind: PromptKind::Label, input: String::new(), error: None });
+            }
+            Some(Action::StopSimulation) => return SimulationHandleResult::Exit,
+            _ => {}
+        }
+        SimulationHandleResult::Continue
+    }
+
+    // a prompt is open, so printable keys are not shortcuts
+    pub fn is_typing(&self) -> bool {
+        self.prompt.is_some() || matches!(self.state, SimulationState::AwaitingInput { .. })
+    }
+
+    pub fn handle_mouse(&mut self, mouse: MouseEvent) {
+        let position = Position::new(mouse.column, mouse.row);
+        let code_area = self.code_area.get();
+
+        if code_area.contains(position) {
+            match mouse.kind {
+                MouseEventKind::ScrollUp => self.scroll_code(-3),
+                MouseEventKind::ScrollDown => self.scroll_code(3),
+                // left click toggles a breakpoint, right click runs to the clicked line
+                MouseEventKind::Down(button) => {
+                    let Some(line) = self.code_line_at(mouse.row) else {
+                        return;
+                    };
+                    match button {
+                        MouseButton::Left => {
+                            if !self.breakpoints.remove(&line) {
+                                self.breakpoints.insert(line);
+                            }
+                        }
+                        MouseButton::Right => self.run_until(Some(line)),
+                        MouseButton::Middle => {}
+                    }
+                }
+                _ => {}
+            }
+        } else if self.memory_area.get().contains(position) {
+            match mouse.kind {
+                MouseEventKind::ScrollUp => self.memory_scroll = self.memory_offset().saturating_sub(3),
+                MouseEventKind::ScrollDown => self.memory_scroll = self.memory_offset() + 3,
+                _ => {}
+            }
+        }
+    }
+
+    // first instruction of the code row drawn at the given terminal row, the flowchart has no lines to click
+    fn code_line_at(&self, row: u16) -> Option<Index> {
+        if self.flowchart.is_some() {
+            return None;
+        }
+        // skip the border and padding
+        let line = self.code_offset() + row.checked_sub(self.code_area.get().y + 2)? as usize;
+        self.code_rows().get(line).map(|row| row.start)
+    }
+
+    // runs until the target line or a breakpoint is reached, or the machine stops
+    fn run_until(&mut self, target: Option<Index>) {
+        if self.state != SimulationState::Running {
+            return;
+        }
+        self.code_scroll = None;
+        self.message = None;
+
+        let options = RunOptions::new()
+            .with_step_limit(CONTINUE_LIMIT)
+            .with_breakpoints(self.breakpoints.iter().copied().chain(target));
+        let report = CrashGuard::new(&self.path, &mut self.machine).run_with(options);
+        match report.outcome {
+            RunOutcome::Halted | RunOutcome::FellOffEnd => self.state = SimulationState::Finished,
+            RunOutcome::Error(message) => self.state = SimulationState::Error(message),
+            RunOutcome::AwaitingInput => self.state = SimulationState::AwaitingInput { value: String::new(), error: None },
+            RunOutcome::LimitExceeded => self.message = Some(format!("Paused after {} steps", report.stats.steps)),
+            RunOutcome::Breakpoint(index) if self.breakpoints.contains(&index) => {
+                let line = self.machine.get_shared_program().line(index) + 1;
+                self.message = Some(format!("Breakpoint on line {}", line));
+            }
+            RunOutcome::Breakpoint(_) => {}
+        }
+    }
+
+    fn handle_prompt(&mut self, key: KeyEvent) -> SimulationHandleResult {
+        let Some(mut prompt) = self.prompt.take() else {
+            return SimulationHandleResult::Continue;
+        };
+
         match key.code {
-            KeyCode::Char(' ') | KeyCode::Enter => {
-                if self.state != SimulationState::Running {
-                    return SimulationHandleResult::Continue;
+            KeyCode::Char(c) => prompt.input.push(c),
+            KeyCode::Backspace => {
+                prompt.input.pop();
+            }
+            KeyCode::Enter => {
+                let result = match prompt.kind {
+                    PromptKind::Watch => Watch::parse(&prompt.input).map(|watch| self.watches.push(watch)),
+                    PromptKind::Label => self.show_label(prompt.input.trim()),
+                };
+                match result {
+                    Ok(()) => return SimulationHandleResult::Continue,
+                    Err(err) => prompt.error = Some(err),
                 }
-                
-                match self.machine.step() {
-                    Ok(true) => {
-                        self.state = SimulationState::Finished;
-                        SimulationHandleResult::Continue
-                    }
-                    Ok(false) => SimulationHandleResult::Continue,
-                    Err(message) => {
-                        self.state = SimulationState::Error(message);
-                        SimulationHandleResult::Continue
+            }
+            KeyCode::Esc => return SimulationHandleResult::Continue,
+            _ => {}
+        }
+        // the prompt stays open until it is confirmed or cancelled
+        self.prompt = Some(prompt);
+        SimulationHandleResult::Continue
+    }
+
+    // scrolls the code pane so the labeled instruction is in the middle
+    fn show_label(&mut self, label: &str) -> Result<(), String> {
+        // multi-word labels are stored with single spaces
+        let label = label.split_whitespace().collect::<Vec<&str>>().join(" ");
+        let index = self.machine.get_shared_program().label(&label)
+            .ok_or_else(|| format!("Label {} not found", label))?;
+        self.code_scroll = Some(self.code_row_of(index).saturating_sub(self.code_height.get() / 2));
+        Ok(())
+    }
+
+    fn handle_awaiting_input(&mut self, key: KeyEvent) -> SimulationHandleResult {
+        let SimulationState::AwaitingInput { value, error } = &mut self.state else {
+            return SimulationHandleResult::Continue;
+        };
+
+        match key.code {
+            KeyCode::Char(c @ ('0'..='9' | '-' | ',' | ' ')) => value.push(c),
+            KeyCode::Backspace => {
+                value.pop();
+            }
+            // push the typed values onto the tape and retry the read
+            KeyCode::Enter => match Parser::parse_tape(value) {
+                Ok(values) if values.is_empty() => {}
+                Ok(values) => {
+                    for value in values {
+                        self.machine.push_input(value);
                     }
+                    self.state = SimulationState::Running;
+                    self.step();
                 }
+                Err(err) => *error = Some(err),
+            },
+            KeyCode::Esc => self.state = SimulationState::Running,
+            _ => {}
+        }
+        SimulationHandleResult::Continue
+    }
+
+    // the rest of a lowered line runs at once unless its instructions are shown
+    fn step_line(&mut self) {
+        self.step();
+        let program = self.machine.get_shared_program().clone();
+        while !self.expanded && self.state == SimulationState::Running {
+            let pointer = self.machine.get_instruction_pointer();
+            if pointer == 0 || pointer >= program.len() || program.line(pointer - 1) != program.line(pointer) {
+                return;
             }
-            KeyCode::Esc => SimulationHandleResult::Exit,
-            _ => SimulationHandleResult::Continue,
+            self.step();
+        }
+    }
+
+    fn step(&mut self) {
+        // stepping brings the instruction pointer back into view
+        self.code_scroll = None;
+        self.message = None;
+
+        if self.machine.is_awaiting_input() {
+            self.state = SimulationState::AwaitingInput { value: String::new(), error: None };
+            return;
+        }
+
+        // a bug in the simulator leaves a report behind before the crash
+        let result = CrashGuard::new(&self.path, &mut self.machine).step();
+
+        match result {
+            Ok(true) => self.state = SimulationState::Finished,
+            Ok(false) => {}
+            Err(message) => self.state = SimulationState::Error(message),
         }
     }
 }
\ No newline at end of file
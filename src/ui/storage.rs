@@ -0,0 +1,10 @@
+use std::path::PathBuf;
+
+// directory for state and configuration files, ~/.rusty_tape or ./.rusty_tape without a home
+pub fn app_dir() -> PathBuf {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".rusty_tape")
+}
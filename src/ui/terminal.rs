@@ -0,0 +1,48 @@
+use std::io;
+use std::panic;
+use std::sync::Once;
+use ratatui::crossterm::cursor::Show;
+use ratatui::crossterm::event::{DisableMouseCapture, EnableMouseCapture};
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
+use crate::ui::crash;
+
+static PANIC_HOOK: Once = Once::new();
+
+// raw mode and the alternate screen for as long as the guard lives, also when unwinding from a panic
+pub struct TerminalGuard;
+
+impl TerminalGuard {
+    pub fn enter() -> io::Result<TerminalGuard> {
+        install_panic_hook();
+        enable_raw_mode()?;
+        // the guard exists before the screen is switched, so a failure below still restores raw mode
+        let guard = TerminalGuard;
+        execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)?;
+        Ok(guard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+// safe to call more than once, errors are ignored since there is nothing left to do about them
+pub fn restore() {
+    let _ = disable_raw_mode();
+    let _ = execute!(io::stdout(), DisableMouseCapture, LeaveAlternateScreen, Show);
+}
+
+// leaves the alternate screen before the panic message is printed, otherwise it is lost with it
+fn install_panic_hook() {
+    PANIC_HOOK.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            restore();
+            crash::record_panic(info.payload());
+            previous(info);
+        }));
+    });
+}
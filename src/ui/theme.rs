@@ -0,0 +1,56 @@
+use ratatui::style::{Color, Modifier, Style};
+
+// styles of the syntax highlighting, selected with `theme = <name>` in the config
+#[derive(Clone, Copy)]
+pub struct Theme {
+    pub label: Style,
+    pub keyword: Style,
+    pub register: Style,
+    pub constant: Style,
+    pub operator: Style,
+    pub comment: Style,
+    pub invalid: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            label: Style::default().fg(Color::Cyan),
+            keyword: Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD),
+            register: Style::default().fg(Color::Yellow),
+            constant: Style::default().fg(Color::LightBlue),
+            operator: Style::default().fg(Color::LightRed),
+            comment: Style::default().fg(Color::DarkGray),
+            invalid: Style::default().fg(Color::Red).add_modifier(Modifier::UNDERLINED),
+        }
+    }
+}
+
+impl Theme {
+    pub fn by_name(name: &str) -> Option<Theme> {
+        match name {
+            "default" => Some(Theme::default()),
+            // darker colors readable on light terminal backgrounds
+            "light" => Some(Theme {
+                label: Style::default().fg(Color::Blue),
+                keyword: Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD),
+                register: Style::default().fg(Color::Rgb(150, 90, 0)),
+                constant: Style::default().fg(Color::Rgb(0, 110, 140)),
+                operator: Style::default().fg(Color::Red),
+                comment: Style::default().fg(Color::Gray).add_modifier(Modifier::ITALIC),
+                invalid: Style::default().fg(Color::Red).add_modifier(Modifier::UNDERLINED),
+            }),
+            // no colors, only text attributes
+            "monochrome" => Some(Theme {
+                label: Style::default().add_modifier(Modifier::UNDERLINED),
+                keyword: Style::default().add_modifier(Modifier::BOLD),
+                register: Style::default(),
+                constant: Style::default(),
+                operator: Style::default(),
+                comment: Style::default().add_modifier(Modifier::DIM | Modifier::ITALIC),
+                invalid: Style::default().add_modifier(Modifier::REVERSED),
+            }),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,61 @@
+#[cfg(test)]
+mod batch_tests {
+    use std::sync::Arc;
+    use rusty_tape::parser::Parser;
+    use rusty_tape::ram::batch::{run_batch, BatchOptions};
+    use rusty_tape::ram::run::RunOutcome;
+
+    const SUM: &str = r"
+        R1 := 0
+        L: R0 := read()
+        if (R0 == 0) goto END
+        R1 := R1 + R0
+        goto L
+        END: write(R1)
+    ";
+
+    #[test]
+    fn test_batch_keeps_input_order() {
+        let program = Arc::new(Parser::new().parse_program(SUM).unwrap());
+        let inputs: Vec<Vec<i32>> = (0..200).map(|n| vec![n, n, 0]).collect();
+
+        let results = run_batch(program, &inputs, BatchOptions::new().with_threads(8));
+        assert_eq!(results.len(), 200);
+        for (n, result) in results.iter().enumerate() {
+            assert_eq!(result.outcome, RunOutcome::FellOffEnd);
+            assert_eq!(result.output, vec![2 * n as i32]);
+            assert_eq!(result.error(), None);
+        }
+    }
+
+    #[test]
+    fn test_batch_failures() {
+        let program = Arc::new(Parser::new().parse_program(SUM).unwrap());
+        let inputs = vec![vec![1, 2, 0], vec![1, 2], vec![1; 100]];
+
+        let results = run_batch(program, &inputs, BatchOptions::new().with_step_limit(50).with_threads(2));
+        assert_eq!(results[0].output, vec![3]);
+        assert_eq!(results[0].steps, 12);
+        assert_eq!(results[1].error(), Some("Attempt to read from empty tape".to_string()));
+        assert_eq!(results[2].outcome, RunOutcome::LimitExceeded);
+        assert_eq!(results[2].steps, 50);
+    }
+
+    #[test]
+    fn test_batch_failing_run() {
+        let program = Arc::new(Parser::new().parse_program("R0 := read()\nR1 := 10\nR1 := R1 / R0\nwrite(R1)").unwrap());
+        let inputs = vec![vec![2], vec![0], vec![5]];
+
+        let results = run_batch(program, &inputs, BatchOptions::new().with_threads(1));
+        assert_eq!(results[0].output, vec![5]);
+        assert_eq!(results[1].outcome, RunOutcome::Error("Division by zero".to_string()));
+        assert_eq!(results[1].steps, 2);
+        assert_eq!(results[2].output, vec![2]);
+    }
+
+    #[test]
+    fn test_batch_without_inputs() {
+        let program = Arc::new(Parser::new().parse_program(SUM).unwrap());
+        assert!(run_batch(program, &[], BatchOptions::new()).is_empty());
+    }
+}
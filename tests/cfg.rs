@@ -0,0 +1,127 @@
+#[cfg(test)]
+mod cfg_tests {
+    use rusty_tape::cfg::{ControlFlowGraph, Edge, EdgeKind, Node};
+    use rusty_tape::parser::Parser;
+
+    fn build(input: &str) -> ControlFlowGraph {
+        ControlFlowGraph::build(&Parser::new().parse_program(input).unwrap())
+    }
+
+    fn edge(from: usize, to: Node, kind: EdgeKind) -> Edge {
+        Edge { from, to, kind }
+    }
+
+    const LOOP: &str = r"
+        R0 := read()
+        R1 := 0
+        loop: if (R0 <= 0) goto end
+              R1 := R1 + R0
+              R0 := R0 - 1
+              goto loop
+        end:  write(R1)
+              halt";
+
+    #[test]
+    fn test_cfg_blocks() {
+        let cfg = build(LOOP);
+        let code: Vec<Vec<&str>> = cfg.blocks.iter()
+            .map(|block| block.code.iter().map(String::as_str).collect())
+            .collect();
+        assert_eq!(code, vec![
+            vec!["R0 := read()", "R1 := 0"],
+            vec!["if (R0 <= 0) goto end"],
+            vec!["R1 := R1 + R0", "R0 := R0 - 1", "goto loop"],
+            vec!["write(R1)", "halt"],
+        ]);
+        assert_eq!(cfg.blocks[1].labels, vec!["loop".to_string()]);
+        assert_eq!(cfg.blocks[3].labels, vec!["end".to_string()]);
+        assert_eq!(cfg.block_of(4), Some(2));
+        // the empty first line of the input
+        assert_eq!(cfg.block_of(0), None);
+
+        assert_eq!(cfg.edges, vec![
+            edge(0, Node::Block(1), EdgeKind::Always),
+            edge(1, Node::Block(3), EdgeKind::Taken),
+            edge(1, Node::Block(2), EdgeKind::NotTaken),
+            edge(2, Node::Block(1), EdgeKind::Always),
+            edge(3, Node::End, EdgeKind::Always),
+        ]);
+    }
+
+    #[test]
+    fn test_cfg_empty_lines_and_edge_cases() {
+        let input = r"
+            # comments and empty lines are not part of any block
+            R0 := 1
+
+            skip:
+            if (R0 == 1) goto next
+            next: goto nowhere
+            R0 := 2";
+        let cfg = build(input);
+
+        assert_eq!(cfg.blocks.len(), 4);
+        assert_eq!(cfg.blocks[0].instructions, vec![2]);
+        // the label on its own line belongs to the block it leads to
+        assert_eq!(cfg.blocks[1].labels, vec!["skip".to_string()]);
+        assert_eq!(cfg.edges, vec![
+            edge(0, Node::Block(1), EdgeKind::Always),
+            // jumping to the next instruction is the same as falling through
+            edge(1, Node::Block(2), EdgeKind::Always),
+            // running past the end of the program
+            edge(3, Node::End, EdgeKind::Always),
+        ]);
+    }
+
+    #[test]
+    fn test_cfg_lowered_expressions() {
+        let cfg = build(r"
+            R0 := (R1 + R2) * 3
+            if (R0 * 2 > R1 - 1) goto end
+            write(R0)
+            end: halt");
+
+        // the instructions of a lowered line stay in one block
+        assert_eq!(cfg.blocks.len(), 3);
+        assert_eq!(cfg.blocks[0].code.last().unwrap(), "if (R-1 > R-2) goto end");
+        assert_eq!(cfg.edges[0], edge(0, Node::Block(2), EdgeKind::Taken));
+    }
+
+    #[test]
+    fn test_cfg_dot() {
+        assert_eq!(build(LOOP).to_dot(), r#"digraph program {
+    node [shape=box, fontname="monospace"];
+    start [shape=oval];
+    end [shape=oval];
+    b0 [label="R0 := read()\lR1 := 0\l"];
+    b1 [label="loop:\lif (R0 <= 0) goto end\l"];
+    b2 [label="R1 := R1 + R0\lR0 := R0 - 1\lgoto loop\l"];
+    b3 [label="end:\lwrite(R1)\lhalt\l"];
+    start -> b0;
+    b0 -> b1;
+    b1 -> b3 [label="yes"];
+    b1 -> b2 [label="no"];
+    b2 -> b1;
+    b3 -> end;
+}
+"#);
+    }
+
+    #[test]
+    fn test_cfg_mermaid() {
+        assert_eq!(build(LOOP).to_mermaid(), r#"flowchart TD
+    start([start])
+    stop([end])
+    b0["R0 := read()<br/>R1 := 0"]
+    b1["loop:<br/>if (R0 #lt;= 0) goto end"]
+    b2["R1 := R1 + R0<br/>R0 := R0 - 1<br/>goto loop"]
+    b3["end:<br/>write(R1)<br/>halt"]
+    start --> b0
+    b0 --> b1
+    b1 -->|yes| b3
+    b1 -->|no| b2
+    b2 --> b1
+    b3 --> stop
+"#);
+    }
+}
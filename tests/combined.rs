@@ -3,7 +3,8 @@ mod combined_tests {
     use rusty_tape::parser::Parser;
     use rusty_tape::ram::instruction::Instruction;
     use rusty_tape::ram::instruction_op::InstructionOp;
-    use rusty_tape::ram::instruction_op::InstructionOp::AssignFromConst;
+    use rusty_tape::ram::instruction_op::InstructionOp::Assign;
+    use rusty_tape::ram::operand::Operand::{Constant, Register};
 
     #[test]
     fn test_multiple_memory_access() {
@@ -17,7 +18,7 @@ mod combined_tests {
             halt
             ";
 
-        let result = Parser::parse_str(input);
+        let result = Parser::new().parse_str(input);
         assert!(result.is_ok());
 
         let mut machine = result.unwrap();
@@ -36,12 +37,12 @@ mod combined_tests {
             R2 := -1";
 
         let instructions = vec![
-            Instruction::new(AssignFromConst(0, -42)),
-            Instruction::new(AssignFromConst(1, 42)),
-            Instruction::new(AssignFromConst(2, -1)),
+            Instruction::new(Assign(Register(0), Constant(-42))),
+            Instruction::new(Assign(Register(1), Constant(42))),
+            Instruction::new(Assign(Register(2), Constant(-1))),
         ];
 
-        let result = Parser::parse_str(input);
+        let result = Parser::new().parse_str(input);
         assert!(result.is_ok());
 
         let mut machine = result.unwrap();
@@ -64,7 +65,7 @@ mod combined_tests {
 
         for (i, (parsed_instr, expected_instr)) in parsed_without_empty.iter().zip(expected).enumerate() {
             assert_eq!(
-                parsed_instr.label, expected_instr.label,
+                parsed_instr.labels, expected_instr.labels,
                 "Label mismatch at instruction {}" , i
             );
             assert_eq!(
@@ -0,0 +1,127 @@
+#[cfg(test)]
+mod compiled_tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use rusty_tape::parser::Parser;
+    use rusty_tape::ram::compiled::{CompiledMachine, CompiledProgram};
+    use rusty_tape::ram::instruction::Instruction;
+    use rusty_tape::ram::instruction_op::InstructionOp;
+    use rusty_tape::ram::op::Op;
+    use rusty_tape::ram::operand::Operand::{self, Constant, Indirect, Register};
+    use rusty_tape::ram::program::Program;
+    use rusty_tape::ram::rel::Rel;
+    use rusty_tape::ram::machine::RamMachine;
+    use rusty_tape::ram::run::RunOptions;
+
+    // runs both interpreters and checks they end in exactly the same state
+    fn assert_equivalent(program: Program, input: Vec<i32>, step_limit: Option<usize>) {
+        let program = Arc::new(program);
+
+        let mut machine = RamMachine::from_program(Arc::clone(&program)).with_input(input.clone());
+        let mut options = RunOptions::new();
+        if let Some(limit) = step_limit {
+            options = options.with_step_limit(limit);
+        }
+        let report = machine.run_with(options);
+
+        let mut compiled = CompiledMachine::new(Arc::new(CompiledProgram::compile(&program)), input);
+        let outcome = compiled.run(step_limit);
+
+        let cells: HashMap<i32, i32> = machine.get_memory().iter()
+            .filter(|(_, &value)| value != 0)
+            .map(|(&address, &value)| (address, value))
+            .collect();
+        assert_eq!(outcome, report.outcome);
+        assert_eq!(compiled.get_steps(), report.stats.steps);
+        assert_eq!(compiled.get_output(), machine.get_output());
+        assert_eq!(compiled.get_input_pointer(), machine.get_input_pointer());
+        assert_eq!(compiled.get_instruction_pointer(), machine.get_instruction_pointer());
+        assert_eq!(compiled.get_cells(), cells);
+    }
+
+    #[test]
+    fn test_compiled_example_programs() {
+        for (file, input) in [
+            ("data/programs/fibonacci.ram", vec![20]),
+            ("data/programs/reverse.ram", vec![4, 8, 15, 16, 23, 42, 0]),
+            ("data/programs/sort.ram", vec![9, -4, 7, 1, 1, 30, -2, 0]),
+            ("data/programs/do_everything.ram", vec![3, 1, 2, 0]),
+        ] {
+            let program = Parser::new().parse_program_file(file).unwrap();
+            assert_equivalent(program.clone(), input.clone(), None);
+            assert_equivalent(program, input, Some(17));
+        }
+    }
+
+    #[test]
+    fn test_compiled_errors() {
+        let program = Parser::new().parse_program("R0 := read()\nwrite(R0)").unwrap();
+        assert_equivalent(program, vec![], None);
+
+        let program = Parser::new().parse_program("R0 := 1\n\n# nothing\nif (R0 == 1) goto NOWHERE\nhalt").unwrap();
+        assert_equivalent(program, vec![], None);
+
+        let program = Parser::new().parse_program("R0 := read()\nR1 := 10 / R0\nR2 := R0 * 1000000\nR2 := R2 * R2").unwrap();
+        assert_equivalent(program.clone(), vec![0], None);
+        assert_equivalent(program, vec![3], None);
+
+        // jumps to empty lines continue at the next instruction, past the end the program stops
+        let program = Program::new(vec![
+            Instruction::new(InstructionOp::Jump("A".to_string())),
+            Instruction::new(InstructionOp::Assign(Register(0), Constant(1))),
+            Instruction::new(InstructionOp::Empty).with_label("A"),
+            Instruction::new(InstructionOp::Assign(Register(1), Constant(2))),
+            Instruction::new(InstructionOp::Jump("B".to_string())),
+            Instruction::new(InstructionOp::Empty).with_label("B"),
+        ]);
+        assert_equivalent(program, vec![], None);
+    }
+
+    #[test]
+    fn test_compiled_random_programs() {
+        // a tiny linear congruential generator keeps the programs reproducible
+        let mut seed: u64 = 0x2545_f491;
+        let mut next = |bound: i32| {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            ((seed >> 33) % bound as u64) as i32
+        };
+        let ops = [Op::Add, Op::Sub, Op::Mul, Op::Div];
+        let rels = [Rel::Lt, Rel::Gt, Rel::Le, Rel::Ge, Rel::Eq, Rel::Ne];
+        let operand = |kind: i32, value: i32| match kind {
+            0 => Operand::Constant(value - 3),
+            1 => Operand::Register(value),
+            _ => Operand::Indirect(value),
+        };
+
+        for _ in 0..200 {
+            let len = 3 + next(12);
+            let mut instructions = Vec::new();
+            for i in 0..len {
+                let reg = next(6);
+                let op = match next(16) {
+                    0 => InstructionOp::Assign(Register(reg), Constant(next(200) - 100)),
+                    1 => InstructionOp::Assign(Register(reg), Register(next(6))),
+                    2 => InstructionOp::Assign(Register(reg), Indirect(next(6))),
+                    3 => InstructionOp::Assign(Indirect(reg), Register(next(6))),
+                    4 => InstructionOp::Arithmetic(Register(reg), Register(next(6)), ops[next(4) as usize], Register(next(6))),
+                    5 => InstructionOp::Arithmetic(Register(reg), Register(next(6)), Op::Div, Constant(1 + next(5))),
+                    6 => InstructionOp::CondJump(Register(reg), rels[next(6) as usize], Constant(next(10) - 5), format!("L{}", next(len + 1))),
+                    7 => InstructionOp::CondJump(Register(reg), rels[next(6) as usize], Register(next(6)), format!("L{}", next(len + 1))),
+                    8 => InstructionOp::Read(Register(reg)),
+                    9 => InstructionOp::Write(Register(reg)),
+                    10 => InstructionOp::Assign(operand(1 + next(2), reg), operand(next(3), next(6))),
+                    11 => InstructionOp::Arithmetic(operand(1 + next(2), reg), operand(next(3), next(6)), ops[next(4) as usize], operand(next(3), next(6))),
+                    12 => InstructionOp::CondJump(operand(next(3), reg), rels[next(6) as usize], operand(next(3), next(6)), format!("L{}", next(len + 1))),
+                    13 => InstructionOp::Read(operand(1 + next(2), reg)),
+                    14 => InstructionOp::Write(operand(next(3), reg)),
+                    _ => InstructionOp::Empty,
+                };
+                instructions.push(Instruction::new(op).with_label(&format!("L{}", i)));
+            }
+
+            let input = (0..next(5)).map(|_| next(50) - 25).collect();
+            // overflow and division by zero end both runs with the same error
+            assert_equivalent(Program::new(instructions), input, Some(500));
+        }
+    }
+}
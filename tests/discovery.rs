@@ -0,0 +1,47 @@
+#[cfg(test)]
+mod discovery_tests {
+    use std::fs;
+    use rusty_tape::ui::discovery::{glob_match, scan};
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("programs/*.ram", "programs/sort.ram"));
+        assert!(glob_match("programs/*.ram", "programs/.ram"));
+        assert!(!glob_match("programs/*.ram", "programs/sort.txt"));
+        // `*` stays within one component
+        assert!(!glob_match("programs/*.ram", "programs/old/sort.ram"));
+
+        assert!(glob_match("test?.ram", "test1.ram"));
+        assert!(!glob_match("test?.ram", "test.ram"));
+        assert!(!glob_match("test?.ram", "test12.ram"));
+
+        assert!(glob_match("data/**/*.ram", "data/sort.ram"));
+        assert!(glob_match("data/**/*.ram", "data/programs/old/sort.ram"));
+        assert!(glob_match("**/sort.ram", "sort.ram"));
+        assert!(!glob_match("data/**/*.ram", "other/sort.ram"));
+    }
+
+    #[test]
+    fn test_scan() {
+        let root = std::env::temp_dir().join(format!("rusty_tape_scan_{}", std::process::id()));
+        for dir in ["programs/old", "target", "extra"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        for file in ["programs/a.ram", "programs/old/b.ram", "programs/notes.txt", "target/c.ram", "extra/d.ram"] {
+            fs::write(root.join(file), "halt").unwrap();
+        }
+        let root_name = root.display().to_string().replace('\\', "/");
+        let path = |file: &str| format!("{}/{}", root_name, file);
+
+        // ignored directories are skipped, files and globs are deduplicated and sorted
+        let found = scan(&[root_name.clone(), path("extra/d.ram")], &["target".to_string()]);
+        assert_eq!(found, vec![path("extra/d.ram"), path("programs/a.ram"), path("programs/old/b.ram")]);
+
+        let found = scan(&[path("*/*.ram")], &[]);
+        assert_eq!(found, vec![path("extra/d.ram"), path("programs/a.ram"), path("target/c.ram")]);
+        let found = scan(&[path("**/b.ram")], &["old".to_string()]);
+        assert!(found.is_empty());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,146 @@
+#[cfg(test)]
+mod expression_tests {
+    use std::sync::Arc;
+    use rusty_tape::parser::Parser;
+    use rusty_tape::ram::compiled::{CompiledMachine, CompiledProgram};
+    use rusty_tape::ram::dialect::Dialect;
+    use rusty_tape::ram::expression::{Expression, Lowering};
+    use rusty_tape::ram::instruction_op::InstructionOp;
+    use rusty_tape::ram::instruction_op::InstructionOp::{Arithmetic, CondJump};
+    use rusty_tape::ram::op::Op;
+    use rusty_tape::ram::operand::Operand::{Constant, Register};
+    use rusty_tape::ram::rel::Rel;
+    use rusty_tape::ram::run::RunOutcome;
+    use rusty_tape::ram::strict_mode::StrictMode;
+
+    fn ops(input: &str) -> Vec<InstructionOp> {
+        let program = Parser::new().parse_program(input).unwrap();
+        program.instructions().iter().map(|instruction| instruction.op.clone()).collect()
+    }
+
+    #[test]
+    fn test_lowering() {
+        assert_eq!(ops("R0 := (R1 + R2) * 3 - R4"), vec![
+            Arithmetic(Register(-1), Register(1), Op::Add, Register(2)),
+            Arithmetic(Register(-1), Register(-1), Op::Mul, Constant(3)),
+            Arithmetic(Register(0), Register(-1), Op::Sub, Register(4)),
+        ]);
+        // the target is only written by the last instruction, so it can appear on the right
+        assert_eq!(ops("R1 := R2 - R1 * 2"), vec![
+            Arithmetic(Register(-1), Register(1), Op::Mul, Constant(2)),
+            Arithmetic(Register(1), Register(2), Op::Sub, Register(-1)),
+        ]);
+        assert_eq!(ops("if (R0 + 1 < R1 * R2) goto end\nend: halt")[..3], [
+            Arithmetic(Register(-1), Register(0), Op::Add, Constant(1)),
+            Arithmetic(Register(-2), Register(1), Op::Mul, Register(2)),
+            CondJump(Register(-1), Rel::Lt, Register(-2), "end".to_string()),
+        ]);
+        // parentheses alone need no temporaries
+        assert_eq!(ops("R0 := (R1 + 2)"), vec![Arithmetic(Register(0), Register(1), Op::Add, Constant(2))]);
+        assert_eq!(ops("R0 := ((R1))"), vec![InstructionOp::Assign(Register(0), Register(1))]);
+    }
+
+    #[test]
+    fn test_lowering_temporaries() {
+        // every level of nesting on the right below the top one keeps one more temporary alive
+        let mut expression = Expression::Operand(Constant(1));
+        for i in 0..17 {
+            expression = Expression::binary(Expression::Operand(Register(i)), Op::Add, expression);
+        }
+        assert!(Lowering::assign(Register(0), expression.clone()).is_ok());
+
+        let deeper = Expression::binary(Expression::Operand(Register(99)), Op::Add, expression);
+        assert_eq!(
+            Lowering::assign(Register(0), deeper).unwrap_err(),
+            "Expression needs more than 16 temporary registers",
+        );
+    }
+
+    #[test]
+    fn test_expression_lines() {
+        let input = r"start: R0 := (R1 + 2) * (R2 - 1)  # compound
+            if (R0 >= 10) goto start
+            write(R0 * 2 + 1)";
+        let err = Parser::new().parse_str(input).unwrap_err();
+        assert_eq!(err, "Line 3: Expected ')' at column 22, found '*'");
+
+        let program = Parser::new().parse_program(&input[..input.rfind('\n').unwrap()]).unwrap();
+        assert_eq!(program.len(), 4);
+        assert_eq!((0..4).map(|i| program.line(i)).collect::<Vec<_>>(), vec![0, 0, 0, 1]);
+        assert_eq!(program.label("start"), Some(0));
+
+        let instructions = program.instructions();
+        assert_eq!(instructions[0].labels, vec!["start".to_string()]);
+        assert_eq!(instructions[0].comment.as_deref(), Some(" compound"));
+        assert!(instructions[1..3].iter().all(|instruction| instruction.labels.is_empty() && instruction.comment.is_none()));
+        assert!(instructions[..3].iter().all(|instruction| instruction.source.as_deref() == Some("R0 := (R1 + 2) * (R2 - 1)")));
+        assert_eq!(instructions[3].source, None);
+    }
+
+    #[test]
+    fn test_run_expressions() {
+        let mut machine = Parser::new().parse_str(r"
+            R1 := read()
+            R0 := 1
+            loop: if (R1 * 2 - 1 <= 0) goto end
+                  R0 := R0 * (R1 + 1) / 2 + (R1 - R1 / 2 * 2)
+                  R1 := R1 - 1
+                  goto loop
+            end:  write(R0)").unwrap().with_input(vec![4]).with_strict_mode(StrictMode::Error);
+
+        assert_eq!(machine.run(), Ok(()));
+        // 4 -> 1 * 5 / 2 + 0 = 2, 3 -> 2 * 4 / 2 + 1 = 5, 2 -> 5 * 3 / 2 + 0 = 7, 1 -> 7 * 2 / 2 + 1 = 8
+        assert_eq!(machine.get_output(), &vec![8]);
+    }
+
+    #[test]
+    fn test_temporaries_are_reserved() {
+        let error = |line: &str| Parser::new().parse_str(line).unwrap_err();
+        assert_eq!(error("R-5 := 1"), "Line 1: Registers R-1 to R-16 are reserved for the temporaries of expressions");
+        assert_eq!(error("R0 := [R-16]"), "Line 1: Registers R-1 to R-16 are reserved for the temporaries of expressions");
+        assert!(Parser::new().parse_str("R-17 := 1\nR0 := 5").is_ok());
+
+        // the temporaries stay out of the memory, the cell -1 reached through a pointer is a separate one
+        let input = r"
+            R5 := -1
+            [R5] := 7
+            R0 := (R1 + 2) * (R2 + 3)
+            write([R5])";
+        let mut machine = Parser::new().parse_str(input).unwrap();
+        assert_eq!(machine.run(), Ok(()));
+        assert_eq!(machine.get_output(), &vec![7]);
+        assert_eq!(machine.get(-1), 7);
+        assert_eq!(machine.get(-2), 0);
+        assert_eq!(machine.get_memory().len(), 3);
+
+        let program = Arc::new(CompiledProgram::compile(&Parser::new().parse_program(input).unwrap()));
+        let mut compiled = CompiledMachine::new(program, vec![]);
+        assert_eq!(compiled.run(None), RunOutcome::FellOffEnd);
+        assert_eq!(compiled.get_cells(), machine.get_memory().clone());
+    }
+
+    #[test]
+    fn test_expression_errors() {
+        let error = |line: &str| Parser::new().parse_str(line).unwrap_err();
+        assert_eq!(error("R0 := (R1 + 2"), "Line 1: Expected ')' at the end of the line");
+        assert_eq!(error("R0 := R1 + * 2"), "Line 1: Expected a register or a number at column 12, found '*'");
+        assert_eq!(error("R0 := ()"), "Line 1: Expected a register or a number at column 8, found ')'");
+
+        let textbook = |line: &str| Parser::new().with_dialect(Dialect::Textbook).parse_str(line).unwrap_err();
+        assert_eq!(textbook("R0 := R1 * R2 + 1"), "Line 1: 'R0 := R1 * R2 + 1' is not a textbook instruction");
+        assert!(Parser::new().with_dialect(Dialect::Textbook).parse_str("R0 := (R1 + 1)").is_ok());
+    }
+
+    #[test]
+    fn test_strict_mode_line_numbers() {
+        let mut machine = Parser::new().parse_str("R0 := R1 * 2 + 1\nR2 := R0 + R3")
+            .unwrap()
+            .with_strict_mode(StrictMode::Warn);
+        assert_eq!(machine.run(), Ok(()));
+        // the temporary R-1 is not reported, the lines are those of the source
+        assert_eq!(machine.get_warnings(), &[
+            "Line 1: Read of uninitialized R1".to_string(),
+            "Line 2: Read of uninitialized R3".to_string(),
+        ]);
+    }
+}
@@ -0,0 +1,44 @@
+#[cfg(test)]
+mod file_browser_tests {
+    use rusty_tape::ui::file_browser::{FileBrowser, FileRow};
+
+    fn names(browser: &FileBrowser) -> Vec<&str> {
+        browser.rows().iter()
+            .filter_map(|row| match row {
+                FileRow::File { name, .. } => Some(name.as_str()),
+                FileRow::Folder { .. } => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(FileBrowser::fuzzy_score("srt", "sort.ram"), Some((11, vec![0, 2, 3])));
+        assert_eq!(FileBrowser::fuzzy_score("SORT", "sort.ram").map(|(_, matched)| matched), Some(vec![0, 1, 2, 3]));
+        assert_eq!(FileBrowser::fuzzy_score("tros", "sort.ram"), None);
+        // runs of characters and word starts beat scattered matches
+        let score = |filter: &str, candidate: &str| FileBrowser::fuzzy_score(filter, candidate).unwrap().0;
+        assert!(score("fib", "fibonacci.ram") > score("fib", "fast_insert_bubble.ram"));
+        assert!(score("sr", "sort_reverse.ram") > score("sr", "insert.ram"));
+    }
+
+    #[test]
+    fn test_filter_ordering() {
+        let mut browser = FileBrowser::new();
+        browser.set_files(vec![
+            "programs/fast_insert_bubble.ram".to_string(),
+            "programs/fibonacci.ram".to_string(),
+            "programs/sort.ram".to_string(),
+            "fib/other.ram".to_string(),
+        ]);
+        for c in "fib".chars() {
+            browser.push_filter(c);
+        }
+        // matches in the file name go before matches in the folder
+        assert_eq!(names(&browser), vec!["fibonacci.ram", "fast_insert_bubble.ram", "other.ram"]);
+        assert_eq!(browser.selected_file(), Some(1));
+
+        browser.clear_filter();
+        assert_eq!(names(&browser), vec!["other.ram", "fast_insert_bubble.ram", "fibonacci.ram", "sort.ram"]);
+    }
+}
@@ -0,0 +1,45 @@
+#[cfg(test)]
+mod keys_tests {
+    use ratatui::crossterm::event::{KeyCode, KeyModifiers};
+    use rusty_tape::ui::keys::{KeyBinding, KeyBindings};
+
+    fn binding(code: KeyCode, modifiers: KeyModifiers) -> Option<KeyBinding> {
+        Some(KeyBinding::new(code, modifiers))
+    }
+
+    #[test]
+    fn test_parse_modifiers() {
+        assert_eq!(KeyBinding::parse("Ctrl+S"), binding(KeyCode::Char('s'), KeyModifiers::CONTROL));
+        assert_eq!(KeyBinding::parse(" ctrl+alt+x "), binding(KeyCode::Char('x'), KeyModifiers::CONTROL | KeyModifiers::ALT));
+        // shift is folded into the character, but kept for other keys
+        assert_eq!(KeyBinding::parse("Shift+w"), binding(KeyCode::Char('W'), KeyModifiers::NONE));
+        assert_eq!(KeyBinding::parse("Shift+Tab"), binding(KeyCode::Tab, KeyModifiers::SHIFT));
+        // a plus on its own is the key, not a separator
+        assert_eq!(KeyBinding::parse("Ctrl++"), binding(KeyCode::Char('+'), KeyModifiers::CONTROL));
+        assert_eq!(KeyBinding::parse("+"), binding(KeyCode::Char('+'), KeyModifiers::NONE));
+    }
+
+    #[test]
+    fn test_parse_named_keys() {
+        assert_eq!(KeyBinding::parse("Enter"), binding(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(KeyBinding::parse("space"), binding(KeyCode::Char(' '), KeyModifiers::NONE));
+        assert_eq!(KeyBinding::parse("Comma"), binding(KeyCode::Char(','), KeyModifiers::NONE));
+        assert_eq!(KeyBinding::parse("PageDown"), binding(KeyCode::PageDown, KeyModifiers::NONE));
+        assert_eq!(KeyBinding::parse("F5"), binding(KeyCode::F(5), KeyModifiers::NONE));
+        assert_eq!(KeyBinding::parse("f"), binding(KeyCode::Char('f'), KeyModifiers::NONE));
+        assert_eq!(KeyBinding::parse("W"), binding(KeyCode::Char('W'), KeyModifiers::NONE));
+        assert_eq!(KeyBinding::parse("Ctrl+PageUp").unwrap().to_string(), "Ctrl+PageUp");
+        assert_eq!(KeyBinding::parse("ctrl+e").unwrap().to_string(), "Ctrl+E");
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert_eq!(KeyBinding::parse(""), None);
+        assert_eq!(KeyBinding::parse("ab"), None);
+        assert_eq!(KeyBinding::parse("Fx"), None);
+        assert_eq!(KeyBinding::parse("Super+S"), None);
+        assert_eq!(KeyBinding::parse("Ctrl+Enterr"), None);
+        // invalid keys are dropped from a list
+        assert_eq!(KeyBindings::parse_list("x, nope, Ctrl+Q").len(), 2);
+    }
+}
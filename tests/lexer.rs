@@ -0,0 +1,98 @@
+#[cfg(test)]
+mod lexer_tests {
+    use rusty_tape::lexer::{label_length, tokenize, Token, TokenKind};
+    use rusty_tape::lexer::TokenKind::*;
+
+    fn kinds(line: &str) -> Vec<TokenKind> {
+        tokenize(line).iter()
+            .map(|token| token.kind)
+            .filter(|kind| *kind != Whitespace)
+            .collect()
+    }
+
+    #[test]
+    fn test_tokenize_is_lossless() {
+        let lines = [
+            "loop:    if (R0 >= 4) goto end   # check",
+            "  [R1] := R-2",
+            "R2 := read()",
+            "?? weird ~ line",
+        ];
+
+        for line in lines {
+            let joined: String = tokenize(line).iter().map(|token| token.text).collect();
+            assert_eq!(joined, line);
+        }
+    }
+
+    #[test]
+    fn test_tokenize_instructions() {
+        assert_eq!(kinds("loop: if (R0 >= 4) goto end"), vec![
+            Identifier, Colon, Keyword, LeftParen, Register, Relation, Number, RightParen, Keyword, Identifier,
+        ]);
+        assert_eq!(kinds("[R1] := R2 * 10"), vec![
+            LeftBracket, Register, RightBracket, Assign, Register, Operator, Number,
+        ]);
+        assert_eq!(kinds("R0 := -42"), vec![Register, Assign, Operator, Number]);
+        assert_eq!(kinds("write(R1) # done"), vec![Keyword, LeftParen, Register, RightParen, Comment]);
+    }
+
+    #[test]
+    fn test_tokenize_registers() {
+        assert_eq!(kinds("R12"), vec![Register]);
+        assert_eq!(kinds("R-3"), vec![Register]);
+        assert_eq!(kinds("R"), vec![Identifier]);
+        assert_eq!(kinds("Rx1"), vec![Identifier]);
+        assert_eq!(kinds("R - 3"), vec![Identifier, Operator, Number]);
+    }
+
+    #[test]
+    fn test_tokenize_relations() {
+        assert_eq!(kinds("< <= > >= == !="), vec![Relation; 6]);
+        assert_eq!(kinds("= !"), vec![Unknown, Unknown]);
+    }
+
+    #[test]
+    fn test_token_positions() {
+        let tokens = tokenize("a: R1");
+        let starts: Vec<usize> = tokens.iter().map(|token| token.start).collect();
+        assert_eq!(starts, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_tokenize_textbook_forms() {
+        assert_eq!(kinds("r0 ∶= r1"), vec![Register, Assign, Register]);
+        assert_eq!(kinds("R0 ← R1"), vec![Register, Assign, Register]);
+        assert_eq!(kinds("≤ ≥ ≠"), vec![Relation; 3]);
+        assert_eq!(kinds("if(R0<R1)goto x"), vec![Keyword, LeftParen, Register, Relation, Register, RightParen, Keyword, Identifier]);
+    }
+
+    #[test]
+    fn test_tokenize_comment_styles() {
+        assert_eq!(kinds("R0 := 1 ; one"), vec![Register, Assign, Number, Comment]);
+        assert_eq!(kinds("R0 := 1 // one"), vec![Register, Assign, Number, Comment]);
+        assert_eq!(kinds("R0 := R1 / 2"), vec![Register, Assign, Register, Operator, Number]);
+    }
+
+    #[test]
+    fn test_label_length() {
+        let length = |line: &str| {
+            let tokens: Vec<Token> = tokenize(line).into_iter().filter(|token| token.kind != Whitespace).collect();
+            label_length(&tokens)
+        };
+        assert_eq!(length("loop: halt"), 1);
+        assert_eq!(length("outer loop 2: halt"), 3);
+        assert_eq!(length("R2: halt"), 1);
+        assert_eq!(length("loop-2: halt"), 3);
+        assert_eq!(length("a.b x: halt"), 4);
+
+        // `-` and `.` only join words they touch on both sides
+        assert_eq!(length("loop -2: halt"), 1);
+        assert_eq!(length("loop- 2: halt"), 1);
+        assert_eq!(length("a-: halt"), 1);
+        assert_eq!(length("a..b: halt"), 1);
+        assert_eq!(length("R0 : = 5"), 1);
+        assert_eq!(length(": halt"), 0);
+        assert_eq!(length("goto end"), 0);
+    }
+}
@@ -0,0 +1,87 @@
+#[cfg(test)]
+mod lint_tests {
+    use rusty_tape::lint::{lint, Lint, Severity};
+    use rusty_tape::parser::Parser;
+
+    fn lints(input: &str) -> Vec<String> {
+        lint(&Parser::new().parse_program(input).unwrap()).iter().map(Lint::to_string).collect()
+    }
+
+    #[test]
+    fn test_lint_clean_program() {
+        let input = r"
+            R0 := read()
+            R1 := 0
+            loop: if (R0 <= 0) goto end
+                  R1 := R1 + R0
+                  R0 := R0 - 1
+                  goto loop
+            end:  write(R1)
+                  halt";
+        assert!(lints(input).is_empty());
+    }
+
+    #[test]
+    fn test_lint_control_flow() {
+        let input = r"
+            R0 := 1
+            goto skip
+            R0 := 2
+            write(R0)
+            skip: write(R0)
+            unused: goto nowhere
+            if (R0 == R0) goto skip
+            if (1 > 2) goto skip";
+
+        assert_eq!(lints(input), vec![
+            "Line 4: warning: This instruction is never reached",
+            "Line 7: error: Label nowhere is not defined",
+            "Line 7: info: Label unused is never used",
+            "Line 8: warning: This instruction is never reached",
+            "Line 8: warning: The condition is always true",
+            "Line 9: warning: The program can run past its end without halt",
+            "Line 9: warning: The condition is always false",
+        ]);
+    }
+
+    #[test]
+    fn test_lint_registers() {
+        let input = r"
+            R1 := 5
+            R1 := 6
+            if (R0 > 0) goto end
+            R2 := R3 + R1
+            end: write(R2)
+            R-20 := 1
+            write(R-20)
+            halt";
+
+        assert_eq!(lints(input), vec![
+            "Line 2: info: The value written to R1 is never read",
+            "Line 4: warning: R0 is read before anything is written to it",
+            "Line 5: warning: R3 is read before anything is written to it",
+            "Line 7: warning: Negative register R-20",
+            "Line 8: warning: Negative register R-20",
+        ]);
+    }
+
+    #[test]
+    fn test_lint_indirect_access() {
+        // an indirect store might write any cell, an indirect load might read any
+        let input = r"
+            R0 := 10
+            R1 := read()
+            [R0] := R1
+            R5 := 3
+            R2 := [R0]
+            write(R2)
+            write(R7)
+            halt";
+        assert!(lints(input).is_empty());
+
+        // the temporaries of lowered expressions are not reported
+        let lints = lint(&Parser::new().parse_program("R0 := read()\nwrite(R0)\nR1 := (R0 + 1) * 2\nwrite(R1)\nhalt").unwrap());
+        assert!(lints.is_empty());
+        assert!(Severity::Error < Severity::Warning && Severity::Warning < Severity::Info);
+    }
+}
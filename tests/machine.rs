@@ -1,18 +1,22 @@
 #[cfg(test)]
 mod machine_tests {
+    use rusty_tape::parser::Parser;
     use rusty_tape::ram::instruction::Instruction;
-    use rusty_tape::ram::instruction_op::InstructionOp::{ArithmeticRegOpConst, ArithmeticRegOpReg, AssignFromConst, AssignFromRegister, CondJumpRegRelConst, CondJumpRegRelReg, Halt, Jump, Load, Read, Store, Write};
+    use rusty_tape::ram::io_mode::IoMode;
+    use rusty_tape::ram::instruction_op::InstructionOp::{Arithmetic, Assign, CondJump, Halt, Jump, Read, Write};
     use rusty_tape::ram::machine::RamMachine;
     use rusty_tape::ram::op::Op;
+    use rusty_tape::ram::operand::Operand::{Constant, Indirect, Register};
     use rusty_tape::ram::rel::Rel;
+    use rusty_tape::ram::run::{RunOptions, RunOutcome};
 
     #[test]
     fn test_assign_from_const() {
         // R0 := 42
         // R1 := -9
         let program = vec![
-            Instruction::new(AssignFromConst(0, 42)),
-            Instruction::new(AssignFromConst(1, -9))
+            Instruction::new(Assign(Register(0), Constant(42))),
+            Instruction::new(Assign(Register(1), Constant(-9)))
         ];
 
         let mut machine = RamMachine::new(program);
@@ -28,9 +32,9 @@ mod machine_tests {
         // R1 := R0
         // R2 := R3  # should set with 0
         let program = vec![
-            Instruction::new(AssignFromConst(0, 42)),
-            Instruction::new(AssignFromRegister(1, 0)),
-            Instruction::new(AssignFromRegister(2, 3))
+            Instruction::new(Assign(Register(0), Constant(42))),
+            Instruction::new(Assign(Register(1), Register(0))),
+            Instruction::new(Assign(Register(2), Register(3)))
         ];
 
         let mut machine = RamMachine::new(program);
@@ -48,11 +52,11 @@ mod machine_tests {
         // R3 := [R0]
         // R4 := [R1]  # nothing here
         let program = vec![
-            Instruction::new(AssignFromConst(0, 2)),
-            Instruction::new(AssignFromConst(1, 42)),
-            Instruction::new(Store(0, 1)),
-            Instruction::new(Load(3, 0)),
-            Instruction::new(Load(4, 1))
+            Instruction::new(Assign(Register(0), Constant(2))),
+            Instruction::new(Assign(Register(1), Constant(42))),
+            Instruction::new(Assign(Indirect(0), Register(1))),
+            Instruction::new(Assign(Register(3), Indirect(0))),
+            Instruction::new(Assign(Register(4), Indirect(1)))
         ];
 
         let mut machine = RamMachine::new(program);
@@ -74,12 +78,12 @@ mod machine_tests {
         //          R5 := R0 * R1
         //          R6 := R0 / R1
         let program = vec![
-            Instruction::new(AssignFromConst(0, 10)).with_label("start"),
-            Instruction::new(AssignFromConst(1, 5)),
-            Instruction::new(ArithmeticRegOpReg(3, 0, Op::Add, 1)),
-            Instruction::new(ArithmeticRegOpReg(4, 0, Op::Sub, 1)),
-            Instruction::new(ArithmeticRegOpReg(5, 0, Op::Mul, 1)),
-            Instruction::new(ArithmeticRegOpReg(6, 0, Op::Div, 1)),
+            Instruction::new(Assign(Register(0), Constant(10))).with_label("start"),
+            Instruction::new(Assign(Register(1), Constant(5))),
+            Instruction::new(Arithmetic(Register(3), Register(0), Op::Add, Register(1))),
+            Instruction::new(Arithmetic(Register(4), Register(0), Op::Sub, Register(1))),
+            Instruction::new(Arithmetic(Register(5), Register(0), Op::Mul, Register(1))),
+            Instruction::new(Arithmetic(Register(6), Register(0), Op::Div, Register(1))),
         ];
 
         let mut machine = RamMachine::new(program);
@@ -98,11 +102,11 @@ mod machine_tests {
         //          R5 := R0 * 5
         //          R6 := R0 / 5
         let program = vec![
-            Instruction::new(AssignFromConst(0, 10)).with_label("start"),
-            Instruction::new(ArithmeticRegOpConst(3, 0, Op::Add, 5)),
-            Instruction::new(ArithmeticRegOpConst(4, 0, Op::Sub, 5)),
-            Instruction::new(ArithmeticRegOpConst(5, 0, Op::Mul, 5)),
-            Instruction::new(ArithmeticRegOpConst(6, 0, Op::Div, 5)),
+            Instruction::new(Assign(Register(0), Constant(10))).with_label("start"),
+            Instruction::new(Arithmetic(Register(3), Register(0), Op::Add, Constant(5))),
+            Instruction::new(Arithmetic(Register(4), Register(0), Op::Sub, Constant(5))),
+            Instruction::new(Arithmetic(Register(5), Register(0), Op::Mul, Constant(5))),
+            Instruction::new(Arithmetic(Register(6), Register(0), Op::Div, Constant(5))),
         ];
 
         let mut machine = RamMachine::new(program);
@@ -123,13 +127,13 @@ mod machine_tests {
         //          R0 := R0 + R2
         // skip:    R0 := R0 + R1
         let program = vec![
-            Instruction::new(AssignFromConst(0, 1)),
-            Instruction::new(AssignFromConst(1, 3)),
-            Instruction::new(AssignFromConst(2, 1000)),
-            Instruction::new(ArithmeticRegOpReg(0, 0, Op::Add, 1)),
+            Instruction::new(Assign(Register(0), Constant(1))),
+            Instruction::new(Assign(Register(1), Constant(3))),
+            Instruction::new(Assign(Register(2), Constant(1000))),
+            Instruction::new(Arithmetic(Register(0), Register(0), Op::Add, Register(1))),
             Instruction::new(Jump("skip".to_string())),
-            Instruction::new(ArithmeticRegOpReg(0, 0, Op::Add, 2)),
-            Instruction::new(ArithmeticRegOpReg(0, 0, Op::Add, 1)).with_label("skip"),
+            Instruction::new(Arithmetic(Register(0), Register(0), Op::Add, Register(2))),
+            Instruction::new(Arithmetic(Register(0), Register(0), Op::Add, Register(1))).with_label("skip"),
         ];
 
         let mut machine = RamMachine::new(program);
@@ -148,13 +152,13 @@ mod machine_tests {
         //          goto start
         // end:     halt
         let program = vec![
-            Instruction::new(AssignFromConst(0, 0)),
-            Instruction::new(AssignFromConst(1, 2)),
-            Instruction::new(AssignFromConst(2, 5)),
-            Instruction::new(CondJumpRegRelConst(2, Rel::Le, 0, "end".to_string()))
+            Instruction::new(Assign(Register(0), Constant(0))),
+            Instruction::new(Assign(Register(1), Constant(2))),
+            Instruction::new(Assign(Register(2), Constant(5))),
+            Instruction::new(CondJump(Register(2), Rel::Le, Constant(0), "end".to_string()))
                 .with_label("start"),
-            Instruction::new(ArithmeticRegOpReg(0, 0, Op::Add, 1)),
-            Instruction::new(ArithmeticRegOpConst(2, 2, Op::Sub, 1)),
+            Instruction::new(Arithmetic(Register(0), Register(0), Op::Add, Register(1))),
+            Instruction::new(Arithmetic(Register(2), Register(2), Op::Sub, Constant(1))),
             Instruction::new(Jump("start".to_string())),
             Instruction::new(Halt).with_label("end")
         ];
@@ -175,12 +179,12 @@ mod machine_tests {
         // true:    R1 := 1
         // end:     halt
         let program = vec![
-            Instruction::new(AssignFromConst(0, 4)),
-            Instruction::new(AssignFromConst(2, 5)),
-            Instruction::new(CondJumpRegRelReg(0, Rel::Lt, 2, "true".to_string())),
-            Instruction::new(AssignFromConst(1, -1)),
+            Instruction::new(Assign(Register(0), Constant(4))),
+            Instruction::new(Assign(Register(2), Constant(5))),
+            Instruction::new(CondJump(Register(0), Rel::Lt, Register(2), "true".to_string())),
+            Instruction::new(Assign(Register(1), Constant(-1))),
             Instruction::new(Jump("end".to_string())),
-            Instruction::new(AssignFromConst(1, 1)).with_label("true"),
+            Instruction::new(Assign(Register(1), Constant(1))).with_label("true"),
             Instruction::new(Halt).with_label("end")
         ];
 
@@ -199,12 +203,12 @@ mod machine_tests {
         // true:    R1 := 1
         // end:     halt
         let program = vec![
-            Instruction::new(AssignFromConst(0, 4)),
-            Instruction::new(AssignFromConst(2, 5)),
-            Instruction::new(CondJumpRegRelReg(0, Rel::Gt, 2, "true".to_string())),
-            Instruction::new(AssignFromConst(1, -1)),
+            Instruction::new(Assign(Register(0), Constant(4))),
+            Instruction::new(Assign(Register(2), Constant(5))),
+            Instruction::new(CondJump(Register(0), Rel::Gt, Register(2), "true".to_string())),
+            Instruction::new(Assign(Register(1), Constant(-1))),
             Instruction::new(Jump("end".to_string())),
-            Instruction::new(AssignFromConst(1, 1)).with_label("true"),
+            Instruction::new(Assign(Register(1), Constant(1))).with_label("true"),
             Instruction::new(Halt).with_label("end")
         ];
 
@@ -219,9 +223,9 @@ mod machine_tests {
         // goto nowhere
         // R1 := 100
         let program = vec![
-            Instruction::new(AssignFromConst(0, 1)),
+            Instruction::new(Assign(Register(0), Constant(1))),
             Instruction::new(Jump("nowhere".to_string())),
-            Instruction::new(AssignFromConst(1, 100)),
+            Instruction::new(Assign(Register(1), Constant(100))),
         ];
 
         let mut machine = RamMachine::new(program);
@@ -248,15 +252,15 @@ mod machine_tests {
         //
         // end:     halt
         let program = vec![
-            Instruction::new(AssignFromConst(0, 0)),
-            Instruction::new(CondJumpRegRelConst(0, Rel::Ge, 4, "end".to_string()))
+            Instruction::new(Assign(Register(0), Constant(0))),
+            Instruction::new(CondJump(Register(0), Rel::Ge, Constant(4), "end".to_string()))
                 .with_label("loop"),
-            Instruction::new(ArithmeticRegOpConst(1, 0, Op::Div, 2)),
-            Instruction::new(CondJumpRegRelConst(1, Rel::Ne, 0, "skip".to_string())),
-            Instruction::new(Read(2)),
-            Instruction::new(ArithmeticRegOpConst(2, 2, Op::Mul, 10)),
-            Instruction::new(Write(2)),
-            Instruction::new(ArithmeticRegOpConst(0, 0, Op::Add, 1))
+            Instruction::new(Arithmetic(Register(1), Register(0), Op::Div, Constant(2))),
+            Instruction::new(CondJump(Register(1), Rel::Ne, Constant(0), "skip".to_string())),
+            Instruction::new(Read(Register(2))),
+            Instruction::new(Arithmetic(Register(2), Register(2), Op::Mul, Constant(10))),
+            Instruction::new(Write(Register(2))),
+            Instruction::new(Arithmetic(Register(0), Register(0), Op::Add, Constant(1)))
                 .with_label("skip"),
             Instruction::new(Jump("loop".to_string())),
             Instruction::new(Halt)
@@ -268,4 +272,141 @@ mod machine_tests {
         assert!(machine.run().is_ok());
         assert_eq!(machine.get_output(), &vec![10, 20]);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_interactive_input() {
+        // R0 := read()
+        // R1 := read()
+        // R2 := R0 + R1
+        // write(R2)
+        let program = vec![
+            Instruction::new(Read(Register(0))),
+            Instruction::new(Read(Register(1))),
+            Instruction::new(Arithmetic(Register(2), Register(0), Op::Add, Register(1))),
+            Instruction::new(Write(Register(2))),
+        ];
+
+        let mut machine = RamMachine::new(program)
+            .with_input(vec![4])
+            .with_io_mode(IoMode::Interactive);
+
+        assert!(!machine.is_awaiting_input());
+        assert_eq!(machine.step(), Ok(false));
+
+        assert!(machine.is_awaiting_input());
+        assert!(machine.step().is_err());
+        assert_eq!(machine.get_instruction_pointer(), 1, "failed read should be retried");
+        assert_eq!(machine.get_instruction_count(), 1);
+
+        machine.push_input(5);
+        assert!(!machine.is_awaiting_input());
+        assert!(machine.run().is_ok());
+        assert_eq!(machine.get_output(), &vec![9]);
+        assert_eq!(machine.get_input(), &vec![4, 5]);
+    }
+
+    #[test]
+    fn test_tape_mode_never_awaits_input() {
+        let program = vec![Instruction::new(Read(Register(0)))];
+
+        let mut machine = RamMachine::new(program);
+        assert!(!machine.is_awaiting_input());
+        assert_eq!(machine.step(), Err("Attempt to read from empty tape".to_string()));
+    }
+
+    #[test]
+    fn test_changed_cells() {
+        // R0 := 1
+        // R1 := R0
+        // R1 := R0
+        // [R0] := R0
+        let program = vec![
+            Instruction::new(Assign(Register(0), Constant(1))),
+            Instruction::new(Assign(Register(1), Register(0))),
+            Instruction::new(Assign(Register(1), Register(0))),
+            Instruction::new(Assign(Indirect(0), Register(0))),
+        ];
+
+        let mut machine = RamMachine::new(program);
+        assert!(machine.get_changed().is_empty());
+
+        machine.step().unwrap();
+        assert_eq!(machine.get_changed(), &[0]);
+        machine.step().unwrap();
+        assert_eq!(machine.get_changed(), &[1]);
+
+        // writing the same value again is not a change
+        machine.step().unwrap();
+        assert!(machine.get_changed().is_empty());
+        machine.step().unwrap();
+        assert!(machine.get_changed().is_empty());
+    }
+
+    #[test]
+    fn test_operands() {
+        let mut machine = Parser::new().parse_str(r"
+            R1 := 10
+            [R1] := read()
+            R2 := 100 - [R1]
+            [R1] := [R1] * R2
+            if (0 < [R1]) goto positive
+            write(-1)
+            positive: write([R1])
+            write(7)
+            ").unwrap().with_input(vec![4]);
+
+        assert_eq!(machine.run(), Ok(()));
+        assert_eq!(machine.get(10), 384);
+        assert_eq!(machine.get_output(), &vec![384, 7]);
+    }
+
+    #[test]
+    fn test_step_limit() {
+        // R0 := 1
+        // loop: R1 := R1 + 1
+        //       if (R0 > 0) goto loop
+        let program = vec![
+            Instruction::new(Assign(Register(0), Constant(1))),
+            Instruction::new(Arithmetic(Register(1), Register(1), Op::Add, Constant(1))).with_label("loop"),
+            Instruction::new(CondJump(Register(0), Rel::Gt, Constant(0), "loop".to_string())),
+        ];
+
+        let mut machine = RamMachine::new(program.clone());
+        assert_eq!(machine.run_with(RunOptions::new().with_step_limit(11)).outcome, RunOutcome::LimitExceeded);
+        assert_eq!(machine.get_instruction_count(), 11);
+        assert_eq!(machine.get(1), 5);
+
+        // the counter keeps changing, so the state never repeats
+        let mut machine = RamMachine::new(program).with_loop_detection();
+        assert_eq!(machine.run_with(RunOptions::new().with_step_limit(1000)).outcome, RunOutcome::LimitExceeded);
+    }
+
+    #[test]
+    fn test_loop_detection() {
+        // R0 := 1
+        // loop: R1 := R0 - 1
+        //       if (R0 > R1) goto loop
+        let program = vec![
+            Instruction::new(Assign(Register(0), Constant(1))),
+            Instruction::new(Arithmetic(Register(1), Register(0), Op::Sub, Constant(1))).with_label("loop"),
+            Instruction::new(CondJump(Register(0), Rel::Gt, Register(1), "loop".to_string())),
+        ];
+
+        let mut machine = RamMachine::new(program).with_loop_detection();
+        assert_eq!(
+            machine.run(),
+            Err("Infinite loop detected: the state at step 1 repeats every 2 steps from line 2".to_string()),
+        );
+        assert_eq!(machine.get_instruction_count(), 3);
+
+        // a program that terminates is not affected
+        let mut machine = Parser::new().parse_str(r"
+            R0 := read()
+            L: if (R0 <= 0) goto END
+            R0 := R0 - 1
+            goto L
+            END: halt
+        ").unwrap().with_input(vec![3]).with_loop_detection();
+        assert!(machine.run().is_ok());
+    }
+}
@@ -2,10 +2,12 @@
 mod parser_tests {
     use std::path::Path;
     use rusty_tape::parser::Parser;
+    use rusty_tape::ram::dialect::Dialect;
     use rusty_tape::ram::instruction::Instruction;
     use rusty_tape::ram::instruction_op::InstructionOp;
-    use rusty_tape::ram::instruction_op::InstructionOp::{ArithmeticRegOpConst, ArithmeticRegOpReg, AssignFromConst, CondJumpRegRelConst, Halt, Jump, Load, Read, Store, Write};
+    use rusty_tape::ram::instruction_op::InstructionOp::{Arithmetic, Assign, CondJump, Halt, Jump, Read, Write};
     use rusty_tape::ram::op::Op;
+    use rusty_tape::ram::operand::Operand::{Constant, Indirect, Register};
     use rusty_tape::ram::rel::Rel;
 
     #[test]
@@ -26,22 +28,22 @@ mod parser_tests {
         end:     halt";
 
         let instructions = vec![
-            Instruction::new(AssignFromConst(0, 0)),
-            Instruction::new(CondJumpRegRelConst(0, Rel::Ge, 4, "end".to_string()))
+            Instruction::new(Assign(Register(0), Constant(0))),
+            Instruction::new(CondJump(Register(0), Rel::Ge, Constant(4), "end".to_string()))
                 .with_label("loop"),
-            Instruction::new(ArithmeticRegOpConst(1, 0, Op::Div, 2)),
-            Instruction::new(CondJumpRegRelConst(1, Rel::Ne, 0, "skip".to_string())),
-            Instruction::new(Read(2)),
-            Instruction::new(ArithmeticRegOpConst(2, 2, Op::Mul, 10)),
-            Instruction::new(Write(2)),
-            Instruction::new(ArithmeticRegOpConst(0, 0, Op::Add, 1))
+            Instruction::new(Arithmetic(Register(1), Register(0), Op::Div, Constant(2))),
+            Instruction::new(CondJump(Register(1), Rel::Ne, Constant(0), "skip".to_string())),
+            Instruction::new(Read(Register(2))),
+            Instruction::new(Arithmetic(Register(2), Register(2), Op::Mul, Constant(10))),
+            Instruction::new(Write(Register(2))),
+            Instruction::new(Arithmetic(Register(0), Register(0), Op::Add, Constant(1)))
                 .with_label("skip"),
             Instruction::new(Jump("loop".to_string())),
             Instruction::new(Halt)
                 .with_label("end"),
         ];
 
-        let result = Parser::parse_str(input);
+        let result = Parser::new().parse_str(input);
         assert!(result.is_ok());
 
         let machine = result.unwrap();
@@ -63,19 +65,19 @@ mod parser_tests {
         end:     halt";
 
         let instructions = vec![
-            Instruction::new(AssignFromConst(0, 0)),
-            Instruction::new(AssignFromConst(1, 2)),
-            Instruction::new(AssignFromConst(2, 5)),
-            Instruction::new(CondJumpRegRelConst(2, Rel::Le, 0, "end".to_string()))
+            Instruction::new(Assign(Register(0), Constant(0))),
+            Instruction::new(Assign(Register(1), Constant(2))),
+            Instruction::new(Assign(Register(2), Constant(5))),
+            Instruction::new(CondJump(Register(2), Rel::Le, Constant(0), "end".to_string()))
                 .with_label("start"),
-            Instruction::new(ArithmeticRegOpReg(0, 0, Op::Add, 1)),
-            Instruction::new(ArithmeticRegOpConst(2, 2, Op::Sub, 1)),
+            Instruction::new(Arithmetic(Register(0), Register(0), Op::Add, Register(1))),
+            Instruction::new(Arithmetic(Register(2), Register(2), Op::Sub, Constant(1))),
             Instruction::new(Jump("start".to_string())),
             Instruction::new(Halt)
                 .with_label("end"),
         ];
 
-        let result = Parser::parse_str(input);
+        let result = Parser::new().parse_str(input);
         assert!(result.is_ok());
 
         let machine = result.unwrap();
@@ -94,14 +96,14 @@ mod parser_tests {
             R3 := [R1]";
 
         let instructions = vec![
-            Instruction::new(AssignFromConst(0, 0)),
-            Instruction::new(AssignFromConst(1, 42)),
-            Instruction::new(Store(0, 1)),
-            Instruction::new(Load(2, 0)),
-            Instruction::new(Load(3, 1)),
+            Instruction::new(Assign(Register(0), Constant(0))),
+            Instruction::new(Assign(Register(1), Constant(42))),
+            Instruction::new(Assign(Indirect(0), Register(1))),
+            Instruction::new(Assign(Register(2), Indirect(0))),
+            Instruction::new(Assign(Register(3), Indirect(1))),
         ];
 
-        let result = Parser::parse_str(input);
+        let result = Parser::new().parse_str(input);
         assert!(result.is_ok());
 
         let machine = result.unwrap();
@@ -122,17 +124,17 @@ mod parser_tests {
         skip:    R0 := R0 + R1";
 
         let instructions = vec![
-            Instruction::new(AssignFromConst(0, 1)),
-            Instruction::new(AssignFromConst(1, 3)),
-            Instruction::new(AssignFromConst(2, 1000)),
-            Instruction::new(ArithmeticRegOpReg(0, 0, Op::Add, 1)),
+            Instruction::new(Assign(Register(0), Constant(1))),
+            Instruction::new(Assign(Register(1), Constant(3))),
+            Instruction::new(Assign(Register(2), Constant(1000))),
+            Instruction::new(Arithmetic(Register(0), Register(0), Op::Add, Register(1))),
             Instruction::new(Jump("skip".to_string())),
-            Instruction::new(ArithmeticRegOpReg(0, 0, Op::Add, 2)),
-            Instruction::new(ArithmeticRegOpReg(0, 0, Op::Add, 1))
+            Instruction::new(Arithmetic(Register(0), Register(0), Op::Add, Register(2))),
+            Instruction::new(Arithmetic(Register(0), Register(0), Op::Add, Register(1)))
                 .with_label("skip"),
         ];
 
-        let result = Parser::parse_str(input);
+        let result = Parser::new().parse_str(input);
         assert!(result.is_ok());
 
         let machine = result.unwrap();
@@ -152,14 +154,14 @@ mod parser_tests {
         }
 
         let instructions = vec![
-            Instruction::new(AssignFromConst(0, 2)),
-            Instruction::new(AssignFromConst(1, 42)),
-            Instruction::new(Store(0, 1)),
-            Instruction::new(Load(3, 0)),
-            Instruction::new(Load(4, 1)),
+            Instruction::new(Assign(Register(0), Constant(2))),
+            Instruction::new(Assign(Register(1), Constant(42))),
+            Instruction::new(Assign(Indirect(0), Register(1))),
+            Instruction::new(Assign(Register(3), Indirect(0))),
+            Instruction::new(Assign(Register(4), Indirect(1))),
         ];
 
-        let result = Parser::parse_file(filename);
+        let result = Parser::new().parse_file(filename);
         assert!(result.is_ok());
 
         let machine = result.unwrap();
@@ -168,6 +170,211 @@ mod parser_tests {
         assert_instructions(program, &instructions);
     }
     
+    #[test]
+    fn test_parse_tape_separators() {
+        assert_eq!(Parser::parse_tape(""), Ok(vec![]));
+        assert_eq!(Parser::parse_tape("1, 2, 3"), Ok(vec![1, 2, 3]));
+        assert_eq!(Parser::parse_tape("1 2\t3"), Ok(vec![1, 2, 3]));
+        assert_eq!(Parser::parse_tape("1\n-2\n3\n"), Ok(vec![1, -2, 3]));
+        assert_eq!(Parser::parse_tape("1,2 3,\n4"), Ok(vec![1, 2, 3, 4]));
+    }
+
+    #[test]
+    fn test_parse_tape_errors() {
+        assert_eq!(Parser::parse_tape("1, 2,"), Err("Input cannot end with a comma.".to_string()));
+        assert_eq!(Parser::parse_tape("1,,2"), Err("Invalid input.".to_string()));
+        assert_eq!(Parser::parse_tape("1, x"), Err("Invalid input.".to_string()));
+        assert_eq!(Parser::parse_tape("3000000000"), Err("Input value 3000000000 is too large.".to_string()));
+        assert_eq!(Parser::parse_tape("-3000000000"), Err("Input value -3000000000 is too small.".to_string()));
+    }
+
+    #[test]
+    fn test_parse_input_directive() {
+        let input = r"
+            # Adds two numbers
+            # input: 4, 5
+
+            R0 := read()
+            # input: 7
+            halt";
+        assert_eq!(Parser::parse_input_directive(input), Some(Ok(vec![4, 5])));
+
+        let input = r"
+            R0 := read()
+            # input: 7
+            halt";
+        assert_eq!(Parser::parse_input_directive(input), None);
+
+        assert!(matches!(Parser::parse_input_directive("# input: 1,"), Some(Err(_))));
+    }
+
+    #[test]
+    fn test_parse_flexible_spacing() {
+        let input = r"
+            R0 := R1+R2
+            R0:=R1 -  3
+            R0 := R1 * -3
+            loop:if(R0<R1)goto loop
+            if ( R0 != 0 ) goto loop
+            R2:=read( )
+            write( R2 )
+            [ R0 ]:=[R1]";
+
+        assert_eq!(
+            Parser::new().with_dialect(Dialect::Textbook).parse_str(input).unwrap_err(),
+            "Line 9: '[R0] := [R1]' is not a textbook instruction",
+        );
+
+        let instructions = vec![
+            Instruction::new(Arithmetic(Register(0), Register(1), Op::Add, Register(2))),
+            Instruction::new(Arithmetic(Register(0), Register(1), Op::Sub, Constant(3))),
+            Instruction::new(Arithmetic(Register(0), Register(1), Op::Mul, Constant(-3))),
+            Instruction::new(CondJump(Register(0), Rel::Lt, Register(1), "loop".to_string())).with_label("loop"),
+            Instruction::new(CondJump(Register(0), Rel::Ne, Constant(0), "loop".to_string())),
+            Instruction::new(Read(Register(2))),
+            Instruction::new(Write(Register(2))),
+        ];
+        let machine = Parser::new().parse_str(&input[..input.rfind('\n').unwrap()]).unwrap();
+        assert_instructions(machine.get_program(), &instructions);
+    }
+
+    #[test]
+    fn test_parse_textbook_forms() {
+        let input = r"
+            ; comments can start with a semicolon
+            r0 ∶= 5          // or two slashes
+            r1 ← r0
+            start: if (r1 ≤ 0) goto end
+            if (r1 ≥ r0) goto end
+            if (r1 ≠ 2) goto start
+            end: halt";
+
+        let instructions = vec![
+            Instruction::new(Assign(Register(0), Constant(5))),
+            Instruction::new(Assign(Register(1), Register(0))),
+            Instruction::new(CondJump(Register(1), Rel::Le, Constant(0), "end".to_string())).with_label("start"),
+            Instruction::new(CondJump(Register(1), Rel::Ge, Register(0), "end".to_string())),
+            Instruction::new(CondJump(Register(1), Rel::Ne, Constant(2), "start".to_string())),
+            Instruction::new(Halt).with_label("end"),
+        ];
+        let machine = Parser::new().parse_str(input).unwrap();
+        assert_instructions(machine.get_program(), &instructions);
+        assert_eq!(machine.get_program()[2].comment.as_deref(), Some(" or two slashes"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |line: &str| Parser::new().parse_str(line).unwrap_err();
+        assert_eq!(error("R0 := R1 +"), "Line 1: Expected a register or a number at the end of the line");
+        assert_eq!(error("R0 = 1"), "Line 1: Expected ':=' at column 4, found '='");
+        assert_eq!(error("if (R0 < 1) goto"), "Line 1: Expected a label at the end of the line");
+        assert_eq!(error("R0 := 3000000000"), "Line 1: Number 3000000000 is out of range");
+        assert_eq!(error("write(R0) halt"), "Line 1: Unexpected 'halt' at column 11");
+        assert_eq!(error("x := 1"), "Line 1: Expected a register at column 1, found 'x'");
+    }
+
+    #[test]
+    fn test_parse_label_lines() {
+        let input = r"
+            R0 := 3
+        outer loop:
+            # the comment keeps its own line
+        check:
+            if (R0 <= 0) goto the end
+            again: retry: R0 := R0 - 1
+            goto outer loop
+        the end:";
+
+        let machine = Parser::new().parse_str(input).unwrap();
+        let program = machine.get_program();
+        assert_eq!(program.len(), 9);
+        assert_eq!(program[5].labels, vec!["outer loop".to_string(), "check".to_string()]);
+        assert_eq!(program[5].op, CondJump(Register(0), Rel::Le, Constant(0), "the end".to_string()));
+        assert_eq!(program[6].labels, vec!["again".to_string(), "retry".to_string()]);
+        assert!(program[2].labels.is_empty() && program[4].labels.is_empty());
+        assert_eq!(program[3].comment.as_deref(), Some(" the comment keeps its own line"));
+
+        // a label on the last line stays there, jumping to it ends the program
+        assert_eq!(program[8].labels, vec!["the end".to_string()]);
+        assert_eq!(program[8].op, InstructionOp::Empty);
+
+        let program = machine.get_shared_program();
+        assert_eq!(program.label("outer loop"), Some(5));
+        assert_eq!(program.label("check"), Some(5));
+        assert_eq!(program.label("retry"), Some(6));
+        assert_eq!(program.label("the end"), Some(8));
+
+        let mut machine = machine;
+        assert!(machine.run().is_ok());
+        assert_eq!(machine.get(0), 0);
+        assert_eq!(machine.get_instruction_count(), 11);
+
+        // labels that look like registers or contain `-` and `.` work after a colon and after goto
+        let input = r"
+        r1: R1 := 1
+        R2: if (R1 == 1) goto loop-2
+        loop-2: goto a.b   x
+        a.b  x: halt
+        goto R2";
+        let program = Parser::new().parse_program(input).unwrap();
+        assert_eq!(program.instructions()[1].labels, vec!["r1".to_string()]);
+        assert_eq!(program.instructions()[1].op, Assign(Register(1), Constant(1)));
+        assert_eq!(program.instructions()[2].op, CondJump(Register(1), Rel::Eq, Constant(1), "loop-2".to_string()));
+        assert_eq!(program.instructions()[3].op, Jump("a.b x".to_string()));
+        assert_eq!(program.label("R2"), Some(2));
+        assert_eq!(program.label("a.b x"), Some(4));
+        assert_eq!(program.instructions()[5].op, Jump("R2".to_string()));
+
+        // anything else before a colon is not a label
+        let error = |line: &str| Parser::new().parse_str(line).unwrap_err();
+        assert_eq!(error("R0 : = 5"), "Line 1: Expected a register at column 6, found '='");
+        assert_eq!(error("loop -2: halt"), "Line 1: Expected a register at column 1, found 'loop'");
+        assert_eq!(error("a-: halt"), "Line 1: Expected a register at column 1, found 'a'");
+        assert_eq!(error("a..b: halt"), "Line 1: Expected a register at column 1, found 'a'");
+        assert_eq!(error("goto a-"), "Line 1: Unexpected '-' at column 7");
+    }
+
+    #[test]
+    fn test_parse_operands() {
+        let input = r"
+            R0 := 5 - R1
+            R0 := [R1] + 1
+            [R2] := R0 * [R1]
+            [R2] := 7
+            write(5)
+            write([R1])
+            [R0] := read()
+            if (5 < R1) goto end
+            if ([R0] == [R1]) goto end
+            R0 := R1 + 2
+            end: write(R0)";
+
+        let instructions = vec![
+            Instruction::new(InstructionOp::Arithmetic(Register(0), Constant(5), Op::Sub, Register(1))),
+            Instruction::new(InstructionOp::Arithmetic(Register(0), Indirect(1), Op::Add, Constant(1))),
+            Instruction::new(InstructionOp::Arithmetic(Indirect(2), Register(0), Op::Mul, Indirect(1))),
+            Instruction::new(InstructionOp::Assign(Indirect(2), Constant(7))),
+            Instruction::new(InstructionOp::Write(Constant(5))),
+            Instruction::new(InstructionOp::Write(Indirect(1))),
+            Instruction::new(InstructionOp::Read(Indirect(0))),
+            Instruction::new(InstructionOp::CondJump(Constant(5), Rel::Lt, Register(1), "end".to_string())),
+            Instruction::new(InstructionOp::CondJump(Indirect(0), Rel::Eq, Indirect(1), "end".to_string())),
+            // forms of the textbook set keep their own variants
+            Instruction::new(Arithmetic(Register(0), Register(1), Op::Add, Constant(2))),
+            Instruction::new(Write(Register(0))).with_label("end"),
+        ];
+        let machine = Parser::new().parse_str(input).unwrap();
+        assert_instructions(machine.get_program(), &instructions);
+        assert_eq!(machine.get_program()[1].op.to_string(), "R0 := 5 - R1");
+        assert_eq!(machine.get_program()[9].op.to_string(), "if ([R0] == [R1]) goto end");
+
+        let error = |line: &str| Parser::new().with_dialect(Dialect::Textbook).parse_str(line).unwrap_err();
+        assert_eq!(error("write(5)"), "Line 1: 'write(5)' is not a textbook instruction");
+        assert_eq!(error("if (5 < R1) goto end"), "Line 1: 'if (5 < R1) goto end' is not a textbook instruction");
+        assert!(Parser::new().with_dialect(Dialect::Textbook).parse_str("R0 := R1 + 2\nif (R0 < 3) goto x\nx: [R0] := R1").is_ok());
+        assert_eq!(Parser::new().parse_str("5 := R0").unwrap_err(), "Line 1: Expected a register at column 1, found '5'");
+    }
+
     fn assert_instructions(parsed: &[Instruction], expected: &Vec<Instruction>) {
         let parsed_without_empty: Vec<Instruction> = parsed.iter()
             .filter(|i| i.op != InstructionOp::Empty).cloned().collect();
@@ -176,7 +383,7 @@ mod parser_tests {
 
         for (i, (parsed_instr, expected_instr)) in parsed_without_empty.iter().zip(expected).enumerate() {
             assert_eq!(
-                parsed_instr.label, expected_instr.label,
+                parsed_instr.labels, expected_instr.labels,
                 "Label mismatch at instruction {}" , i
             );
             assert_eq!(
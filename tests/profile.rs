@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod profile_tests {
+    use rusty_tape::parser::Parser;
+    use rusty_tape::ram::profile::{AccessCount, BranchCount};
+
+    fn run_profiled(code: &str, input: Vec<i32>) -> rusty_tape::ram::machine::RamMachine {
+        let mut machine = Parser::new().parse_str(code).unwrap().with_input(input).with_profiling();
+        machine.run().unwrap();
+        machine
+    }
+
+    #[test]
+    fn test_profile_disabled_by_default() {
+        let mut machine = Parser::new().parse_str("R0 := 1\nhalt").unwrap();
+        machine.run().unwrap();
+        assert!(machine.get_profile().is_none());
+    }
+
+    #[test]
+    fn test_profile_counts() {
+        let machine = run_profiled(r"
+            R0 := 3
+            L: if (R0 <= 0) goto END
+            R0 := R0 - 1
+            goto L
+            END: halt
+        ", vec![]);
+        let profile = machine.get_profile().unwrap();
+
+        assert_eq!(profile.executions(1), 1);
+        assert_eq!(profile.executions(2), 4);
+        assert_eq!(profile.executions(3), 3);
+        assert_eq!(profile.executions(4), 3);
+        assert_eq!(profile.executions(5), 1);
+        assert_eq!(profile.max_executions(), 4);
+
+        assert_eq!(profile.branch(2), Some(BranchCount { taken: 1, not_taken: 3 }));
+        assert_eq!(profile.branch(4), None);
+        assert_eq!(profile.access(0), AccessCount { reads: 7, writes: 4 });
+        assert_eq!(profile.access(1), AccessCount::default());
+    }
+
+    #[test]
+    fn test_profile_indirect_access() {
+        let machine = run_profiled(r"
+            R0 := 5
+            R1 := read()
+            [R0] := R1
+            R2 := [R0]
+            halt
+        ", vec![7]);
+        let profile = machine.get_profile().unwrap();
+
+        assert_eq!(profile.access(0), AccessCount { reads: 2, writes: 1 });
+        assert_eq!(profile.access(5), AccessCount { reads: 1, writes: 1 });
+        assert_eq!(profile.access(2), AccessCount { reads: 0, writes: 1 });
+    }
+
+    #[test]
+    fn test_profile_reports() {
+        let machine = run_profiled("R0 := 1\nif (R0 == 1) goto L\nL: halt", vec![]);
+        let profile = machine.get_profile().unwrap();
+
+        let text = profile.to_text(machine.get_shared_program());
+        assert!(text.starts_with("Instructions executed: 3\n"));
+        assert!(text.contains("R0 := 1"));
+
+        let json = profile.to_json(machine.get_shared_program());
+        assert!(json.contains("\"instructions_executed\": 3"));
+        assert!(json.contains("{\"index\": 1, \"line\": 2, \"taken\": 1, \"not_taken\": 0}"));
+        assert!(json.contains("{\"address\": 0, \"reads\": 1, \"writes\": 1}"));
+        assert!(json.trim_start().starts_with('{') && json.trim_end().ends_with('}'));
+
+        // lowered lines and empty lines make the index differ from the line
+        let machine = run_profiled("# sum\n\nR0 := (1 + 2) * 3\nwrite(R0)", vec![]);
+        let json = machine.get_profile().unwrap().to_json(machine.get_shared_program());
+        assert!(json.contains("{\"index\": 3, \"line\": 3, \"count\": 1, \"instruction\": \"R0 := R-1 * 3\"}"));
+        assert!(json.contains("{\"index\": 4, \"line\": 4, \"count\": 1, \"instruction\": \"write(R0)\"}"));
+    }
+}
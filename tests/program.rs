@@ -0,0 +1,64 @@
+#[cfg(test)]
+mod program_tests {
+    use std::sync::Arc;
+    use rusty_tape::parser::Parser;
+    use rusty_tape::ram::execution::Execution;
+    use rusty_tape::ram::machine::RamMachine;
+
+    const DOUBLE: &str = r"
+        L: R0 := read()
+        if (R0 == 0) goto END
+        R0 := R0 * 2
+        write(R0)
+        goto L
+        END: halt
+    ";
+
+    #[test]
+    fn test_parse_program() {
+        let program = Parser::new().parse_program(DOUBLE).unwrap();
+        assert_eq!(program.len(), 8);
+        assert_eq!(program.label("L"), Some(1));
+        assert_eq!(program.label("END"), Some(6));
+        assert_eq!(program.label("MISSING"), None);
+        assert_eq!(Parser::new().parse_program("R0 := ").unwrap_err(), Parser::new().parse_str("R0 := ").unwrap_err());
+    }
+
+    #[test]
+    fn test_shared_program() {
+        let program = Arc::new(Parser::new().parse_program(DOUBLE).unwrap());
+
+        for n in 1..=5 {
+            let mut machine = RamMachine::from_program(Arc::clone(&program)).with_input(vec![n, n + 1, 0]);
+            machine.run().unwrap();
+            assert_eq!(machine.get_output(), &vec![2 * n, 2 * n + 2]);
+            assert!(Arc::ptr_eq(machine.get_shared_program(), &program));
+        }
+        assert_eq!(Arc::strong_count(&program), 1);
+    }
+
+    #[test]
+    fn test_clone_and_resume() {
+        let mut machine = Parser::new().parse_str(DOUBLE).unwrap().with_input(vec![1, 2, 0]);
+        for _ in 0..4 {
+            machine.step().unwrap();
+        }
+
+        // a clone continues independently of the original
+        let mut copy = machine.clone();
+        copy.push_input(7);
+        copy.run().unwrap();
+        assert_eq!(copy.get_output(), &vec![2, 4]);
+        assert_eq!(machine.get_output(), &vec![2]);
+
+        let state: Execution = machine.clone().into_state();
+        assert_eq!(state.get_output(), &vec![2]);
+        assert_eq!(state.get_input_pointer(), 1);
+        assert_eq!(state.get(0), 2);
+
+        let mut resumed = RamMachine::resume(Arc::clone(machine.get_shared_program()), state);
+        resumed.run().unwrap();
+        assert_eq!(resumed.get_output(), &vec![2, 4]);
+        assert_eq!(resumed.get_instruction_count(), machine.get_instruction_count() + 9);
+    }
+}
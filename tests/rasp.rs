@@ -0,0 +1,141 @@
+#[cfg(test)]
+mod rasp_tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use rusty_tape::parser::Parser;
+    use rusty_tape::ram::execution_mode::ExecutionMode;
+    use rusty_tape::ram::instruction_op::InstructionOp;
+    use rusty_tape::ram::machine::RamMachine;
+    use rusty_tape::ram::operand::Operand::{Constant, Register};
+    use rusty_tape::ram::rasp;
+
+    #[test]
+    fn test_encode_decode_roundtrip() {
+        let input = r"
+                 R0 := read()
+                 R1 := 0
+                 R2 := 1
+        L:       if (R0 <= 0) goto END
+                 R3 := R2
+                 R2 := R3 + R1
+                 R1 := R3
+                 R0 := R0 - 1
+                 [R1] := R0
+                 R4 := [R2]
+                 if (R4 != R1) goto L
+                 goto L
+        END:     write(R1)
+                 halt";
+
+        let machine = Parser::new().parse_str(input).unwrap();
+        let program = machine.get_program();
+
+        let mut labels = HashMap::new();
+        labels.insert("L".to_string(), 4);
+        labels.insert("END".to_string(), 13);
+        let names = labels.iter().map(|(label, &index)| (index, label.clone())).collect();
+
+        let cells = rasp::encode(program, &labels).unwrap();
+        assert_eq!(cells.len(), program.len() * rasp::INSTRUCTION_WIDTH as usize);
+
+        for (i, instruction) in program.iter().enumerate() {
+            let chunk = &cells[i * 4..i * 4 + 4];
+            let decoded = rasp::decode([chunk[0], chunk[1], chunk[2], chunk[3]], &names).unwrap();
+            assert_eq!(decoded, instruction.op, "Roundtrip mismatch at instruction {}", i);
+        }
+    }
+
+    #[test]
+    fn test_decode_invalid_opcode() {
+        assert!(rasp::decode([7, 0, 0, 0], &HashMap::new()).is_err());
+        assert!(rasp::decode([48, 0, 0, 0], &HashMap::new()).is_err());
+        assert!(rasp::decode([30, -1, 0, 0], &HashMap::new()).is_err());
+        assert_eq!(rasp::decode([30, 5, 0, 0], &HashMap::new()), Ok(InstructionOp::Jump("5".to_string())));
+    }
+
+    #[test]
+    fn test_rasp_runs_like_ram() {
+        let input = r"
+                 R0 := 0  # sum
+                 R1 := 2  # increment
+                 R2 := 5  # counter
+        start:   if (R2 <= 0) goto end
+                 R0 := R0 + R1
+                 R2 := R2 - 1
+                 goto start
+        end:     halt";
+
+        let mut machine = Parser::new().parse_str(input).unwrap()
+            .with_mode(ExecutionMode::Rasp(rasp::DEFAULT_BASE))
+            .unwrap();
+
+        // the leading empty line is encoded too, the first assignment follows it
+        assert_eq!(machine.get(rasp::DEFAULT_BASE + 4), 1, "program should be loaded into memory");
+
+        machine.run().unwrap();
+        assert_eq!(machine.get(0), 10);
+        assert_eq!(machine.get(2), 0);
+    }
+
+    #[test]
+    fn test_self_modifying_program() {
+        // `R2 := 5` is the fifth instruction after the leading empty line, its constant is the third cell
+        let base = rasp::DEFAULT_BASE;
+        let constant = base + 4 * rasp::INSTRUCTION_WIDTH + 2;
+        let input = format!(r"
+            R0 := {}
+            R1 := 42
+            [R0] := R1
+            R2 := 5
+            halt", constant);
+        let input = input.as_str();
+
+        let mut machine = Parser::new().parse_str(input).unwrap()
+            .with_mode(ExecutionMode::Rasp(base))
+            .unwrap();
+        machine.run().unwrap();
+        assert_eq!(machine.get(2), 42);
+        assert_eq!(machine.instruction_at(4).unwrap(), InstructionOp::Assign(Register(2), Constant(42)));
+
+        // a resumed run keeps the mode and the modified program in memory
+        let mut machine = Parser::new().parse_str(input).unwrap()
+            .with_mode(ExecutionMode::Rasp(base))
+            .unwrap();
+        for _ in 0..3 {
+            machine.step().unwrap();
+        }
+        let state = machine.clone().into_state();
+        assert_eq!(state.get_mode(), ExecutionMode::Rasp(base));
+        let mut resumed = RamMachine::resume(Arc::clone(machine.get_shared_program()), state);
+        resumed.run().unwrap();
+        assert_eq!(resumed.get_mode(), ExecutionMode::Rasp(base));
+        assert_eq!(resumed.get(2), 42);
+
+        // the same program without RASP is unaffected
+        let mut machine = Parser::new().parse_str(input).unwrap();
+        machine.run().unwrap();
+        assert_eq!(machine.get(2), 5);
+    }
+
+    #[test]
+    fn test_rasp_unknown_label() {
+        let input = r"
+            goto nowhere
+            halt";
+
+        let result = Parser::new().parse_str(input).unwrap()
+            .with_mode(ExecutionMode::Rasp(rasp::DEFAULT_BASE));
+        assert!(result.is_err());
+
+        let result = Parser::new().parse_str("halt").unwrap()
+            .with_mode(ExecutionMode::Rasp(-4));
+        assert_eq!(result.unwrap_err(), "RASP base -4 is negative");
+    }
+
+    #[test]
+    fn test_rasp_extended_operands() {
+        let result = Parser::new().parse_str("write([R1])").unwrap()
+            .with_mode(ExecutionMode::Rasp(rasp::DEFAULT_BASE));
+        assert_eq!(result.unwrap_err(), "'write([R1])' has no RASP encoding");
+    }
+}
@@ -0,0 +1,97 @@
+#[cfg(test)]
+mod run_tests {
+    use rusty_tape::parser::Parser;
+    use rusty_tape::ram::io_mode::IoMode;
+    use rusty_tape::ram::machine::RamMachine;
+    use rusty_tape::ram::run::{RunOptions, RunOutcome, RunStats};
+
+    fn countdown() -> RamMachine {
+        Parser::new().parse_str(r"
+            R0 := read()
+            L: if (R0 <= 0) goto END
+            write(R0)
+            R0 := R0 - 1
+            goto L
+            END: halt
+        ").unwrap()
+    }
+
+    #[test]
+    fn test_run_outcomes() {
+        let report = countdown().with_input(vec![2]).run_with(RunOptions::new());
+        assert_eq!(report.outcome, RunOutcome::Halted);
+        assert_eq!(report.stats, RunStats { steps: 11, instruction_count: 11, inputs_read: 1, outputs_written: 2, cells_used: 1 });
+
+        let mut machine = Parser::new().parse_str("R0 := 1\nR1 := 2").unwrap();
+        assert_eq!(machine.run_with(RunOptions::new()).outcome, RunOutcome::FellOffEnd);
+
+        let mut machine = countdown();
+        assert_eq!(
+            machine.run_with(RunOptions::new()).outcome,
+            RunOutcome::Error("Attempt to read from empty tape".to_string()),
+        );
+        assert!(machine.run().is_err());
+
+        // arithmetic errors stop the program like any other error
+        let mut machine = Parser::new().parse_str("R0 := read()\nR1 := 7 / R0").unwrap().with_input(vec![0]);
+        assert_eq!(machine.run_with(RunOptions::new()).outcome, RunOutcome::Error("Division by zero".to_string()));
+        assert_eq!(machine.get_instruction_pointer(), 1);
+        let mut machine = Parser::new().parse_str("R0 := 2147483647\nR0 := R0 + 1").unwrap();
+        assert_eq!(machine.run(), Err("Overflow in 2147483647 + 1".to_string()));
+    }
+
+    #[test]
+    fn test_run_step_limit() {
+        let mut machine = countdown().with_input(vec![100]);
+        let report = machine.run_with(RunOptions::new().with_step_limit(10));
+        assert_eq!(report.outcome, RunOutcome::LimitExceeded);
+        assert_eq!(report.stats.steps, 10);
+        assert!(!report.outcome.is_finished());
+
+        // the limit counts the steps of a single run
+        let report = machine.run_with(RunOptions::new().with_step_limit(10));
+        assert_eq!(report.stats.steps, 10);
+        assert_eq!(report.stats.instruction_count, 20);
+    }
+
+    #[test]
+    fn test_run_breakpoints() {
+        let mut machine = countdown().with_input(vec![2]);
+        let options = || RunOptions::new().with_breakpoints([3]);
+
+        let report = machine.run_with(options());
+        assert_eq!(report.outcome, RunOutcome::Breakpoint(3));
+        assert_eq!(report.stats.steps, 2);
+        assert!(machine.get_output().is_empty());
+
+        // resuming executes the instruction under the breakpoint
+        assert_eq!(machine.run_with(options()).outcome, RunOutcome::Breakpoint(3));
+        assert_eq!(machine.get_output(), &vec![2]);
+        assert_eq!(machine.run_with(options()).outcome, RunOutcome::Halted);
+        assert_eq!(machine.get_output(), &vec![2, 1]);
+    }
+
+    #[test]
+    fn test_run_observer() {
+        let mut pointers = Vec::new();
+        let report = countdown().with_input(vec![1]).run_with(
+            RunOptions::new().with_observer(|machine| pointers.push(machine.get_instruction_pointer())),
+        );
+        assert_eq!(report.outcome, RunOutcome::Halted);
+        assert_eq!(pointers, vec![2, 3, 4, 5, 2, 6, 6]);
+    }
+
+    #[test]
+    fn test_run_io_mode() {
+        let mut machine = countdown();
+        let report = machine.run_with(RunOptions::new().with_io_mode(IoMode::Interactive));
+        assert_eq!(report.outcome, RunOutcome::AwaitingInput);
+        assert_eq!(report.stats.steps, 0);
+        // the machine keeps its own mode for later runs
+        assert_eq!(machine.get_io_mode(), IoMode::Tape);
+
+        machine.push_input(1);
+        assert_eq!(machine.run_with(RunOptions::new()).outcome, RunOutcome::Halted);
+        assert_eq!(machine.get_output(), &vec![1]);
+    }
+}
@@ -0,0 +1,58 @@
+#[cfg(test)]
+mod session_tests {
+    use std::fs;
+    use std::path::PathBuf;
+    use rusty_tape::ui::session::{load_presets, preset_path, save_preset, InputHistory};
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("rusty_tape_{}_{}", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_input_history() {
+        let path = temp_path("history");
+        let _ = fs::remove_file(&path);
+
+        let mut history = InputHistory::load_from(path.clone());
+        assert!(history.get("sort.ram").is_empty());
+        for tape in ["1 2", "3", "4 5 6"] {
+            history.record("sort.ram", tape).unwrap();
+        }
+        history.record("fib.ram", "10").unwrap();
+        // recording a tape again moves it to the front instead of repeating it
+        history.record("sort.ram", "1 2").unwrap();
+        assert_eq!(history.get("sort.ram"), ["1 2", "4 5 6", "3"]);
+
+        // only the most recent ones are kept, also after loading them again
+        for tape in ["7", "8", "9"] {
+            history.record("sort.ram", tape).unwrap();
+        }
+        let history = InputHistory::load_from(path.clone());
+        assert_eq!(history.get("sort.ram"), ["9", "8", "7", "1 2", "4 5 6"]);
+        assert_eq!(history.get("fib.ram"), ["10"]);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_presets() {
+        let program = temp_path("presets.ram").display().to_string();
+        let _ = fs::remove_file(preset_path(&program));
+
+        assert!(load_presets(&program).is_empty());
+        save_preset(&program, " small ", "1 2").unwrap();
+        save_preset(&program, "large", "1 2 3 4 5").unwrap();
+        // a preset of the same name is replaced, the others keep their order
+        save_preset(&program, "small", "0").unwrap();
+        assert_eq!(load_presets(&program), vec![
+            ("large".to_string(), "1 2 3 4 5".to_string()),
+            ("small".to_string(), "0".to_string()),
+        ]);
+
+        assert!(save_preset(&program, " ", "1").is_err());
+        assert!(save_preset(&program, "a=b", "1").is_err());
+        assert_eq!(load_presets(&program).len(), 2);
+
+        fs::remove_file(preset_path(&program)).unwrap();
+    }
+}
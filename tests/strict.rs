@@ -0,0 +1,70 @@
+#[cfg(test)]
+mod strict_tests {
+    use rusty_tape::parser::Parser;
+    use rusty_tape::ram::strict_mode::StrictMode;
+
+    const SLOPPY: &str = r"
+        R1 := R1 + 1
+        R2 := -1
+        [R2] := R1
+        R3 := 5000
+        [R3] := R1
+        write(R1)
+    ";
+
+    #[test]
+    fn test_strict_off() {
+        let mut machine = Parser::new().parse_str(SLOPPY).unwrap();
+        assert!(machine.run().is_ok());
+        assert_eq!(machine.get(-1), 1);
+        assert!(machine.get_warnings().is_empty());
+    }
+
+    #[test]
+    fn test_strict_warn() {
+        let mut machine = Parser::new().parse_str(SLOPPY).unwrap()
+            .with_strict_mode(StrictMode::Warn)
+            .with_memory_limit(4096);
+        assert!(machine.run().is_ok());
+        assert_eq!(machine.get_output(), &vec![1]);
+        assert_eq!(machine.get_warnings(), &[
+            "Line 2: Read of uninitialized R1".to_string(),
+            "Line 4: Negative address -1".to_string(),
+            "Line 6: Address 5000 is beyond the memory limit of 4096".to_string(),
+        ]);
+    }
+
+    #[test]
+    fn test_strict_error() {
+        let mut machine = Parser::new().parse_str(SLOPPY).unwrap().with_strict_mode(StrictMode::Error);
+        assert_eq!(machine.run(), Err("Line 2: Read of uninitialized R1".to_string()));
+
+        let mut machine = Parser::new().parse_str(&SLOPPY.replacen("R1 := R1 + 1", "R1 := 1", 1)).unwrap()
+            .with_strict_mode(StrictMode::Error);
+        assert_eq!(machine.run(), Err("Line 4: Negative address -1".to_string()));
+        assert_eq!(machine.get(-1), 0);
+
+        // without a memory limit any positive address is fine
+        let mut machine = Parser::new().parse_str("R0 := 5000\n[R0] := R0\nR1 := [R0]").unwrap()
+            .with_strict_mode(StrictMode::Error);
+        assert!(machine.run().is_ok());
+        let mut machine = Parser::new().parse_str("R0 := 5000\n[R0] := R0").unwrap()
+            .with_strict_mode(StrictMode::Error)
+            .with_memory_limit(5000);
+        assert_eq!(machine.run(), Err("Line 2: Address 5000 is beyond the memory limit of 5000".to_string()));
+    }
+
+    #[test]
+    fn test_strict_warnings_once_per_line() {
+        let mut machine = Parser::new().parse_str(r"
+            L: R1 := R2 + 1
+            R0 := R0 + 1
+            if (R0 < 10) goto L
+        ").unwrap().with_strict_mode(StrictMode::Warn);
+        assert!(machine.run().is_ok());
+        assert_eq!(machine.get_warnings(), &[
+            "Line 2: Read of uninitialized R2".to_string(),
+            "Line 3: Read of uninitialized R0".to_string(),
+        ]);
+    }
+}
@@ -0,0 +1,41 @@
+#[cfg(test)]
+mod watch_tests {
+    use rusty_tape::parser::Parser;
+    use rusty_tape::ram::watch::{Address, Watch, MAX_RANGE};
+
+    #[test]
+    fn test_parse_watch() {
+        assert_eq!(Watch::parse("R3"), Ok(Watch::Cell(Address::Direct(3))));
+        assert_eq!(Watch::parse(" [R1] "), Ok(Watch::Cell(Address::Indirect(1))));
+        assert_eq!(Watch::parse("R3..R10"), Ok(Watch::Range(Address::Direct(3), Address::Direct(10))));
+        assert_eq!(Watch::parse("[R0] .. [R1]"), Ok(Watch::Range(Address::Indirect(0), Address::Indirect(1))));
+        assert_eq!(Watch::parse("R-1"), Ok(Watch::Cell(Address::Direct(-1))));
+
+        assert_eq!(Watch::parse("R"), Err("Invalid watch: R".to_string()));
+        assert!(Watch::parse("R1..").is_err());
+        assert!(Watch::parse("[R1").is_err());
+        assert!(Watch::parse("3").is_err());
+    }
+
+    #[test]
+    fn test_watch_display() {
+        for watch in ["R3", "[R1]", "R3..R10", "[R0]..[R1]"] {
+            assert_eq!(Watch::parse(watch).unwrap().to_string(), watch);
+        }
+    }
+
+    #[test]
+    fn test_watch_addresses() {
+        let mut machine = Parser::new().parse_str(r"
+            R0 := 10
+            R1 := 13
+            R2 := 20000").unwrap();
+        machine.run().unwrap();
+
+        assert_eq!(Watch::parse("R1").unwrap().addresses(&machine), vec![1]);
+        assert_eq!(Watch::parse("[R0]").unwrap().addresses(&machine), vec![10]);
+        assert_eq!(Watch::parse("[R0]..[R1]").unwrap().addresses(&machine), vec![10, 11, 12, 13]);
+        assert_eq!(Watch::parse("[R1]..[R0]").unwrap().addresses(&machine), Vec::<i32>::new());
+        assert_eq!(Watch::parse("R0..[R2]").unwrap().addresses(&machine).len(), MAX_RANGE);
+    }
+}